@@ -1,11 +1,27 @@
 mod scaffold;
+mod wizard;
 
-use anyhow::{bail, Result};
-use clap::{Parser, Subcommand, ValueEnum};
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
 use colored::*;
-use scaffold::{scaffold_project, scaffold_project_embedded};
-use std::path::PathBuf;
-use include_dir::{include_dir, Dir};
+use minijinja::Value;
+use scaffold::conflicts::ConflictStrategy;
+use scaffold::render::{detect_author, RenderContext};
+use scaffold::catalog::Catalog;
+use scaffold::config::{self as settings_file, Settings};
+use scaffold::manifest::TemplateManifest;
+use scaffold::metadata;
+use scaffold::generate;
+use scaffold::hooks::{confirm_hooks, run_hooks};
+use scaffold::vcs::{self, Vcs};
+use scaffold::{
+    available_templates, find_template, find_template_by_name, pack, preview, project_drift, render_project,
+    scaffold_into_existing,
+    scaffold_project, template_dependencies, update_project, user_templates_dir, TemplateLocation,
+};
+use scaffold::validate::validate_package_name;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -22,31 +38,237 @@ pub enum Commands {
         /// The name of the project to create
         name: String,
 
-        /// The database to use for the project
-        #[arg(long, value_enum, help = "Database type")]
-        db: Option<Database>,
+        #[command(flatten)]
+        options: ProjectOptions,
 
-        /// The frontend framework to use
-        #[arg(long, value_enum, default_value_t = Frontend::Dioxus, help = "Frontend framework")]
-        frontend: Frontend,
+        /// Generate into the directory even if it already exists
+        #[arg(long)]
+        force: bool,
+
+        /// How to handle files that already exist (with --force)
+        #[arg(long, value_enum, requires = "force")]
+        conflict: Option<ConflictStrategy>,
+
+        /// Render the template in memory and print the files it would write, without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// With --dry-run, also print a unified diff against DIR (defaults to the project path)
+        #[arg(long, value_name = "DIR", num_args = 0..=1, requires = "dry_run")]
+        diff: Option<Option<PathBuf>>,
+    },
+    /// Add Trestle to an existing directory (defaults to the current one)
+    Init {
+        /// The directory to initialize
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        #[command(flatten)]
+        options: ProjectOptions,
+
+        /// How to handle files that already exist (defaults to asking, or skipping when not interactive)
+        #[arg(long, value_enum)]
+        conflict: Option<ConflictStrategy>,
+    },
+    /// Merge changes of the project's template into a generated project
+    Update {
+        /// The project directory (containing .trestle/project.toml)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Update to this template instead of the latest version of the recorded one: a git
+        /// URL (git+file:///srv/house.git#v3), a directory or .tar.gz archive, or a template id
+        #[arg(long, value_name = "PATH|ID")]
+        template: Option<String>,
+
+        /// Directory of the template inside a --template repository, directory or archive
+        #[arg(long, value_name = "PATH", requires = "template")]
+        subdir: Option<PathBuf>,
+
+        /// Print what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show how a generated project differs from its template (exits with 1 on drift)
+    Diff {
+        /// The project directory (containing .trestle/project.toml)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Only print a summary of changed files
+        #[arg(long)]
+        stat: bool,
+    },
+    /// Add code to a generated project
+    #[command(visible_alias = "g")]
+    Generate {
+        #[command(subcommand)]
+        command: GenerateCommand,
+    },
+    /// List, package, install and manage templates
+    #[command(visible_alias = "templates")]
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+    /// Show or change the defaults in config.toml and .trestle.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
     },
 }
 
-#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
-#[clap(rename_all = "kebab_case")]
-pub enum Database {
-    Postgres,
-    Mysql,
-    Mongodb,
-    Firebase,
+#[derive(Subcommand, Debug)]
+pub enum GenerateCommand {
+    /// Scaffold a model with list/show/new/edit/delete pages, e.g. `resource Post title:string body:text`
+    Resource {
+        /// The resource's type name, e.g. Post or BlogPost
+        name: String,
+
+        /// Fields as name:type; types are string, text, bool, int, bigint and float
+        #[arg(value_name = "FIELD:TYPE")]
+        fields: Vec<String>,
+
+        /// The project directory (containing .trestle/project.toml)
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        project: PathBuf,
+    },
+    /// Add empty up/down SQL files to migrations/ of a PostgreSQL or MySQL project
+    Migration {
+        /// What the migration does, e.g. add_tags_to_posts
+        name: String,
+
+        /// The project directory (containing .trestle/project.toml)
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        project: PathBuf,
+    },
 }
 
-#[derive(ValueEnum, Clone, Debug, Copy, Default, PartialEq, Eq)]
-#[clap(rename_all = "kebab_case")]
-pub enum Frontend {
-    #[default]
-    Dioxus,
-    Htmx,
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the value in effect for a key (exits with 1 when it is not set)
+    Get {
+        /// The key, e.g. frontend or addons.tailwind
+        key: String,
+    },
+    /// Set a key in the user config, or in the repo's .trestle.toml with --repo
+    Set {
+        /// The key, e.g. frontend or addons.tailwind
+        key: String,
+
+        /// The value; template_paths takes a comma separated list
+        value: String,
+
+        /// Write to the nearest .trestle.toml (created in the current directory if there is none)
+        #[arg(long)]
+        repo: bool,
+    },
+    /// List every value in effect and the file it comes from
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Pack a template directory into a .tar.gz archive with a .sha256 checksum
+    Pack {
+        /// The template directory (containing trestle-template.toml)
+        dir: PathBuf,
+
+        /// Where to write the archive (defaults to <id>-<version>.tar.gz)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Install a packed template into the user template directory
+    Install {
+        /// The .tar.gz archive to install
+        archive: PathBuf,
+
+        /// Replace an installed template with the same id
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove an installed template
+    Remove {
+        /// Id of the template to remove
+        id: String,
+    },
+    /// List every available template and the frontend × database combinations they cover
+    List {
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Frontend used when neither --frontend nor --template is given and no wizard runs.
+const DEFAULT_FRONTEND: &str = "dioxus";
+
+/// Options shared by every command that generates a project.
+#[derive(Args, Debug)]
+pub struct ProjectOptions {
+    /// Package name to use in Cargo.toml, if it should differ from the directory name
+    #[arg(long = "name", value_name = "PACKAGE")]
+    pub package_name: Option<String>,
+
+    /// The database to use for the project (e.g. postgres, mysql, mongodb, firebase)
+    #[arg(long, help = "Database type")]
+    pub db: Option<String>,
+
+    /// The frontend framework to use (e.g. dioxus, htmx). Without --frontend, --db or
+    /// --template, a wizard asks in a terminal; otherwise it defaults to dioxus
+    #[arg(long, help = "Frontend framework [default: dioxus]")]
+    pub frontend: Option<String>,
+
+    /// Use a specific template: a git URL (git+file:///srv/house.git#v2), a directory or
+    /// .tar.gz archive, or the id of an installed template
+    #[arg(long, value_name = "PATH|ID", conflicts_with_all = ["db", "frontend"])]
+    pub template: Option<String>,
+
+    /// Directory of the template inside a --template repository, directory or archive
+    #[arg(long, value_name = "PATH", requires = "template")]
+    pub subdir: Option<PathBuf>,
+
+    /// Enable an optional add-on declared by the template (repeatable)
+    #[arg(long = "addon", value_name = "NAME")]
+    pub addons: Vec<String>,
+
+    /// Disable an add-on that the template enables by default (repeatable)
+    #[arg(long = "no-addon", value_name = "NAME")]
+    pub no_addons: Vec<String>,
+
+    /// Author for Cargo.toml and the LICENSE file, as `Name <email>` (defaults to the config,
+    /// then git's user.name and user.email)
+    #[arg(long, value_name = "AUTHOR")]
+    pub author: Option<String>,
+
+    /// License of the project as an SPDX expression, e.g. `MIT OR Apache-2.0`; writes the
+    /// matching LICENSE file(s)
+    #[arg(long, value_name = "SPDX")]
+    pub license: Option<String>,
+
+    /// Package description for Cargo.toml
+    #[arg(long)]
+    pub description: Option<String>,
+
+    /// Repository URL for Cargo.toml (defaults to the origin remote of an existing directory)
+    #[arg(long, value_name = "URL")]
+    pub repository: Option<String>,
+
+    /// Do not run the template's post-generation hooks
+    #[arg(long)]
+    pub no_hooks: bool,
+
+    /// Run the shell commands among the hooks of a template that is not built in without asking
+    #[arg(long, conflicts_with = "no_hooks")]
+    pub allow_hooks: bool,
+
+    /// Version control to set up in the project (defaults to none)
+    #[arg(long, value_enum)]
+    pub vcs: Option<Vcs>,
+
+    /// With --vcs git, commit the generated files
+    #[arg(long)]
+    pub commit: bool,
 }
 
 fn main() -> Result<()> {
@@ -55,66 +277,424 @@ fn main() -> Result<()> {
     match &cli.command {
         Commands::New {
             name,
-            db,
-            frontend,
+            options,
+            force,
+            conflict,
+            dry_run,
+            diff,
         } => {
             let project_path = PathBuf::from(name);
+            if *dry_run {
+                let diff = diff
+                    .as_ref()
+                    .map(|dir| dir.clone().unwrap_or_else(|| project_path.clone()));
+                return generate(&project_path, options, WriteMode::DryRun { diff });
+            }
+            if project_path.exists() && !force {
+                anyhow::bail!(
+                    "Directory '{}' already exists. Use `trestle init {}` or `--force` to generate into it.",
+                    project_path.display(),
+                    name
+                );
+            }
+            let mode = if project_path.exists() {
+                WriteMode::Existing(conflict.unwrap_or_else(ConflictStrategy::default_for_terminal))
+            } else {
+                WriteMode::Fresh
+            };
+            generate(&project_path, options, mode)?;
+        }
+        Commands::Init {
+            path,
+            options,
+            conflict,
+        } => {
+            let mode = if path.exists() {
+                WriteMode::Existing(conflict.unwrap_or_else(ConflictStrategy::default_for_terminal))
+            } else {
+                WriteMode::Fresh
+            };
+            generate(path, options, mode)?;
+        }
+        Commands::Update {
+            path,
+            template,
+            subdir,
+            dry_run,
+        } => {
+            let location = match template {
+                Some(name) => Some(find_template_by_name(name, subdir.as_deref())?.1),
+                None => None,
+            };
+            let summary = update_project(path, location, *dry_run)?;
+            summary.print();
+            if *dry_run {
+                println!("{}", "\nDry run: nothing was written.".dimmed());
+            } else if !summary.conflicted.is_empty() {
+                println!(
+                    "\n{} {} file(s) need attention before committing.",
+                    "!".yellow(),
+                    summary.conflicted.len()
+                );
+            }
+        }
+        Commands::Diff { path, stat } => {
+            let drift = project_drift(path)?;
+            if drift.is_empty() {
+                println!("{} No drift from the template.", "✓".green());
+            } else {
+                if *stat {
+                    drift.print_stat();
+                } else {
+                    drift.print();
+                }
+                std::process::exit(1);
+            }
+        }
+        Commands::Generate { command } => match command {
+            GenerateCommand::Resource { name, fields, project } => {
+                println!("{} Generating resource '{}'...", "✓".green(), name.bold().cyan());
+                generate::generate_resource(project, name, fields)?.print();
+            }
+            GenerateCommand::Migration { name, project } => {
+                println!("{} Generating migration '{}'...", "✓".green(), name.bold().cyan());
+                generate::generate_migration(project, name)?.print();
+            }
+        },
+        Commands::Template { command } => run_template_command(command)?,
+        Commands::Config { command } => run_config_command(command)?,
+    }
 
-            // Extract just the final component as the project name
-            let project_name = project_path
-                .file_name()
-                .ok_or_else(|| anyhow::anyhow!("Invalid project path: {}", name))?
-                .to_str()
-                .ok_or_else(|| anyhow::anyhow!("Project name contains invalid UTF-8"))?;
+    Ok(())
+}
+
+/// How `generate` should write the rendered project.
+enum WriteMode {
+    /// Create a new directory.
+    Fresh,
+    /// Write into a directory that already exists, resolving conflicts with the strategy.
+    Existing(ConflictStrategy),
+    /// Only print what would be written, optionally diffed against a directory.
+    DryRun { diff: Option<PathBuf> },
+}
 
+/// Render the selected template and write it to `project_path` according to `mode`.
+fn generate(project_path: &Path, options: &ProjectOptions, mode: WriteMode) -> Result<()> {
+    // Extract just the final component as the project name, unless --name overrides it.
+    // An existing path is canonicalized first so that `.` resolves to the directory's name.
+    let resolved_path = project_path
+        .canonicalize()
+        .unwrap_or_else(|_| project_path.to_path_buf());
+    let project_name = match &options.package_name {
+        Some(package_name) => package_name.as_str(),
+        None => resolved_path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid project path: {}", project_path.display()))?
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Project name contains invalid UTF-8"))?,
+    };
+    // Fail before the wizard asks anything; clashes with the template's dependencies are
+    // checked once a template is chosen.
+    validate_package_name(project_name, &[])?;
+
+    // CLI flags win over the repo's .trestle.toml, which wins over the user config.
+    let settings = Settings::load_current()?;
+    let vcs = options.vcs.or(settings.vcs()).unwrap_or(Vcs::None);
+    if options.commit && vcs != Vcs::Git {
+        anyhow::bail!("--commit needs --vcs git");
+    }
+    let frontend = options.frontend.clone().or(settings.frontend());
+    let db = match &options.db {
+        Some(db) => (db != "none").then(|| db.clone()),
+        None => settings.db().flatten(),
+    };
+
+    let mut author = options
+        .author
+        .clone()
+        .or(settings.author())
+        .or_else(detect_author)
+        .unwrap_or_default();
+    let mut license = options.license.clone().or(settings.license());
+    let repository = options.repository.clone().or_else(|| vcs::origin_url(project_path));
+
+    // Resolve the template from --template, from the wizard when nothing was chosen on
+    // a terminal, or from the manifests of the available templates by frontend/database
+    let interactive = options.template.is_none()
+        && frontend.is_none()
+        && options.db.is_none()
+        && settings.db().is_none()
+        && wizard::available();
+    let mut package_name = project_name.to_string();
+    let (manifest, location, addons) = if interactive {
+        let templates = available_templates()?;
+        let dependencies = |manifest: &TemplateManifest, location: &TemplateLocation, addons: &BTreeMap<String, bool>| {
+            let mut context = RenderContext::new(project_name);
+            context
+                .insert("frontend", manifest.frontend.as_str())
+                .insert("db", Value::from(manifest.db.clone()))
+                .insert("addons", Value::from_serialize(addons));
+            template_dependencies(&context, location)
+        };
+        let Some(answers) = wizard::run(
+            project_name,
+            &templates,
+            &author,
+            license.as_deref(),
+            |manifest| resolve_addons(manifest, options, &settings),
+            dependencies,
+        )?
+        else {
+            println!("Aborted; nothing was written.");
+            return Ok(());
+        };
+        package_name = answers.name;
+        author = answers.author;
+        license = answers.license;
+        (answers.manifest, answers.location, answers.addons)
+    } else {
+        let (manifest, location) = match &options.template {
+            Some(name) => find_template_by_name(name, options.subdir.as_deref()),
+            None => find_template(frontend.as_deref().unwrap_or(DEFAULT_FRONTEND), db.as_deref()),
+        }
+        .map_err(|e| anyhow::anyhow!(e.to_string().red().to_string()))?;
+        let addons = resolve_addons(&manifest, options, &settings)?;
+        (manifest, location, addons)
+    };
+    let project_name = package_name.as_str();
+
+    println!(
+        "🔥 Initializing new Trestle project '{}' ({})...",
+        project_name.bold().cyan(),
+        manifest.id
+    );
+    if let Some(license) = &license {
+        let unknown = metadata::unknown_licenses(license);
+        if !unknown.is_empty() {
             println!(
-                "🔥 Initializing new Trestle project '{}'...",
-                project_name.bold().cyan()
+                "{} No license text for {}; add the LICENSE file yourself.",
+                "!".yellow(),
+                unknown.join(", ")
             );
+        }
+    }
 
-            // Determine the template directory based on flags
-            let template_name = match (frontend, db) {
-                (Frontend::Htmx, None) => "static-htmx",
-                (Frontend::Dioxus, None) => "static-dioxus",
-                (Frontend::Htmx, Some(Database::Postgres)) => "postgres-htmx",
-                (Frontend::Dioxus, Some(Database::Postgres)) => "postgres-dioxus",
-                (Frontend::Htmx, Some(Database::Mysql)) => "mysql-htmx",
-                (Frontend::Dioxus, Some(Database::Mysql)) => "mysql-dioxus",
-                (Frontend::Htmx, Some(Database::Mongodb)) => "mongodb-htmx",
-                (Frontend::Dioxus, Some(Database::Mongodb)) => "mongodb-dioxus",
-                (Frontend::Htmx, Some(Database::Firebase)) => "firebase-htmx",
-                (Frontend::Dioxus, Some(Database::Firebase)) => "firebase-dioxus", 
-            };
+    let mut context = RenderContext::new(project_name);
+    context
+        .insert("author", author)
+        .insert("license", Value::from(license))
+        .insert("description", Value::from(options.description.clone()))
+        .insert("repository", Value::from(repository))
+        .insert("frontend", manifest.frontend.as_str())
+        .insert("db", Value::from(manifest.db.clone()))
+        .insert("addons", Value::from_serialize(addons));
+
+    // Shell commands from templates that do not ship with Trestle only run with consent.
+    let needs_consent = location.source.kind() != "embedded"
+        && !options.allow_hooks
+        && manifest.hooks.iter().any(|hook| hook.runs_shell());
 
-            // Prefer embedded templates (works in crates.io installs). Fallback to FS for local dev.
-            static TEMPLATES: Dir = include_dir!("templates");
-            if TEMPLATES.get_dir(template_name).is_some() {
-                scaffold_project_embedded(&project_path, project_name, template_name, &TEMPLATES)?;
+    if let WriteMode::DryRun { diff } = &mode {
+        let project = render_project(&context, &location)?;
+        println!("\n{}", preview::tree(&project_path.display().to_string(), &project));
+        if let Some(dir) = diff {
+            let diff = preview::diff_against(&project, dir)?;
+            if diff.is_empty() {
+                println!("No changes compared to '{}'.", dir.display());
             } else {
-                let template_path = PathBuf::from("templates").join(template_name);
-                if !template_path.exists() {
-                    let error_msg = format!(
-                        "Template '{}' not found in embedded assets or on disk!",
-                        template_path.display()
-                    );
-                    bail!(error_msg.red().to_string());
-                }
+                preview::print_diff(&diff);
+            }
+        }
+        if !manifest.hooks.is_empty() && !options.no_hooks {
+            let title = if needs_consent {
+                "Hooks that would run (after confirming, or with --allow-hooks):"
+            } else {
+                "Hooks that would run:"
+            };
+            println!("{}", title.bold());
+            for hook in &manifest.hooks {
+                println!("   {} {}", "→".cyan(), hook.command(&context)?);
+            }
+        }
+        println!("{}", "Dry run: nothing was written.".dimmed());
+        return Ok(());
+    }
+
+    let mut steps = Vec::new();
+    if resolved_path.canonicalize().ok() != std::env::current_dir().ok() {
+        steps.push(format!("cd {}", project_path.display()));
+    }
+    let with_hooks = !options.no_hooks
+        && !manifest.hooks.is_empty()
+        && (!needs_consent || confirm_hooks(&manifest.hooks, &manifest.id, &context)?);
+    if needs_consent && !with_hooks && !options.no_hooks {
+        println!(
+            "{} Skipped the hooks of template '{}', which is not built in; pass --allow-hooks to run them.",
+            "!".yellow(),
+            manifest.id
+        );
+    }
+    if !with_hooks {
+        // Skipped hooks become steps to run by hand.
+        for hook in &manifest.hooks {
+            steps.push(hook.command(&context)?);
+        }
+    }
 
-                scaffold_project(&project_path, project_name, &template_path)?;
+    // For a new project this runs in the staging directory, which is only moved into place
+    // once it succeeded.
+    let finish = |dir: &Path| -> Result<()> {
+        // A repository is only committed to when Trestle created it, never an enclosing one.
+        let mut created_repository = false;
+        if vcs == Vcs::Git {
+            created_repository = vcs::git_init(dir)?;
+            if created_repository {
+                println!("{} Initialized a git repository", "✓".green());
+            } else {
+                println!(
+                    "{} '{}' is already inside a git repository; skipped git init",
+                    "!".yellow(),
+                    project_path.display()
+                );
             }
+        }
+        if with_hooks {
+            println!();
+            run_hooks(&manifest.hooks, dir, &context)?;
+        }
+        if options.commit && created_repository {
+            let message = format!("Initial commit from Trestle template '{}'", manifest.id);
+            let commit = vcs::initial_commit(dir, &message)?;
+            println!("{} Committed the generated files ({})", "✓".green(), commit);
+        }
+        Ok(())
+    };
+    match mode {
+        WriteMode::Existing(strategy) => {
+            scaffold_into_existing(project_path, &context, &location, strategy)?.print();
+            finish(project_path)?;
+        }
+        _ => scaffold_project(project_path, &context, &location, finish)?,
+    }
+
+    println!("\n🎉 Success! Your project is ready.");
+    println!("\nNext steps:");
+    steps.extend(manifest.next_steps.iter().cloned());
+    for (i, step) in steps.iter().enumerate() {
+        println!("   {}. {}", i + 1, step.cyan());
+    }
+
+    Ok(())
+}
 
-            println!("\n🎉 Success! Your project is ready.");
-            println!("\nNext steps:");
-            println!("   1. {}", format!("cd {}", name).cyan());
-            if db.is_some() {
-                println!("   2. {}", "cp .env.example .env".cyan());
-                println!("   3. {}", "Update .env with your credentials".cyan());
-                println!("   4. {}", "cargo run".cyan());
+/// The add-on selection: --addon/--no-addon, then the configured defaults for add-ons the
+/// template has, then the template's own defaults.
+fn resolve_addons(
+    manifest: &TemplateManifest,
+    options: &ProjectOptions,
+    settings: &Settings,
+) -> Result<BTreeMap<String, bool>> {
+    let mut enabled = options.addons.clone();
+    let mut disabled = options.no_addons.clone();
+    for (name, on) in settings.addons() {
+        let chosen = enabled.contains(&name) || disabled.contains(&name);
+        if chosen || !manifest.addons.contains_key(&name) {
+            continue;
+        }
+        if on {
+            enabled.push(name);
+        } else {
+            disabled.push(name);
+        }
+    }
+    manifest.resolve_addons(&enabled, &disabled)
+}
+
+fn run_config_command(command: &ConfigCommand) -> Result<()> {
+    let settings = Settings::load_current()?;
+    match command {
+        ConfigCommand::Get { key } => match settings.get(key)? {
+            Some((value, _)) => println!("{}", value),
+            None => std::process::exit(1),
+        },
+        ConfigCommand::Set { key, value, repo } => {
+            let path = if *repo {
+                let cwd = std::env::current_dir()?;
+                settings_file::find_repo_config(&cwd)
+                    .unwrap_or_else(|| cwd.join(settings_file::REPO_CONFIG_FILE))
             } else {
-                println!("   2. {}", "cargo run".cyan());
+                settings_file::user_config_dir()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Could not determine the user config directory; set XDG_CONFIG_HOME")
+                    })?
+                    .join(settings_file::USER_CONFIG_FILE)
+            };
+            settings_file::set(&path, key, value)?;
+            println!("{} Set {} = {} in {}", "✓".green(), key.bold(), value, path.display());
+        }
+        ConfigCommand::List => {
+            let values = settings.list();
+            if values.is_empty() {
+                println!("No configuration set. Known keys:");
+                for (key, description) in settings_file::KEYS {
+                    println!("   {:<16} {}", key.bold(), description.dimmed());
+                }
+            }
+            for (key, value, path) in values {
+                println!("{} = {}  {}", key, value, format!("# {}", path.display()).dimmed());
             }
         }
     }
+    Ok(())
+}
 
+fn run_template_command(command: &TemplateCommand) -> Result<()> {
+    let templates_dir = || {
+        user_templates_dir().ok_or_else(|| {
+            anyhow::anyhow!("Could not determine the user template directory; set XDG_CONFIG_HOME")
+        })
+    };
+
+    match command {
+        TemplateCommand::Pack { dir, output } => {
+            let packed = pack::pack(dir, output.as_deref())?;
+            println!(
+                "{} Packed '{}' into '{}'",
+                "✓".green(),
+                packed.manifest.id.bold().cyan(),
+                packed.archive.display()
+            );
+            println!("   sha256 {}", packed.sha256);
+        }
+        TemplateCommand::Install { archive, force } => {
+            let installed = pack::install(archive, &templates_dir()?, *force)?;
+            if !installed.verified {
+                println!(
+                    "{} No {} file next to the archive; checksum not verified.",
+                    "!".yellow(),
+                    pack::CHECKSUM_SUFFIX
+                );
+            }
+            println!(
+                "{} Installed '{}' into '{}'",
+                "✓".green(),
+                installed.manifest.id.bold().cyan(),
+                installed.dir.display()
+            );
+            println!("   Use it with: trestle new <name> --template {}", installed.manifest.id);
+        }
+        TemplateCommand::Remove { id } => {
+            let dir = pack::remove(&templates_dir()?, id)?;
+            println!("{} Removed '{}' ({})", "✓".green(), id, dir.display());
+        }
+        TemplateCommand::List { json } => {
+            let catalog = Catalog::new(available_templates()?);
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&catalog)?);
+            } else {
+                catalog.print();
+            }
+        }
+    }
     Ok(())
-}
\ No newline at end of file
+}
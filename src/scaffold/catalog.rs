@@ -0,0 +1,180 @@
+use super::manifest::TemplateManifest;
+use super::TemplateLocation;
+use colored::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Column of the matrix used for templates without a database.
+const NO_DB: &str = "none";
+
+/// Where a listed template comes from.
+#[derive(Debug, Serialize)]
+pub struct SourceInfo {
+    /// `embedded`, `directory`, `archive` or `git`.
+    pub kind: &'static str,
+    pub location: String,
+}
+
+/// One available template, as printed by `trestle template list`.
+#[derive(Debug, Serialize)]
+pub struct CatalogEntry {
+    #[serde(flatten)]
+    pub manifest: TemplateManifest,
+    pub source: SourceInfo,
+}
+
+/// Every available template plus the frontend × database combinations they cover.
+#[derive(Debug, Serialize)]
+pub struct Catalog {
+    pub templates: Vec<CatalogEntry>,
+    /// frontend -> database (`none` for static sites) -> template id.
+    pub matrix: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Catalog {
+    pub fn new(templates: Vec<(TemplateManifest, TemplateLocation)>) -> Self {
+        let mut matrix: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        for (manifest, _) in &templates {
+            matrix
+                .entry(manifest.frontend.clone())
+                .or_default()
+                .entry(manifest.db.clone().unwrap_or_else(|| NO_DB.to_string()))
+                .or_insert_with(|| manifest.id.clone());
+        }
+
+        let mut templates: Vec<CatalogEntry> = templates
+            .into_iter()
+            .map(|(manifest, location)| {
+                let source = SourceInfo {
+                    kind: location.source.kind(),
+                    location: location.source.describe(),
+                };
+                CatalogEntry { manifest, source }
+            })
+            .collect();
+        templates.sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
+
+        Self { templates, matrix }
+    }
+
+    /// Database columns of the matrix: `none` first, then the rest by name.
+    fn databases(&self) -> Vec<&str> {
+        let dbs: BTreeSet<&str> = self
+            .matrix
+            .values()
+            .flat_map(|row| row.keys().map(String::as_str))
+            .collect();
+        let mut columns: Vec<&str> = dbs.iter().copied().filter(|db| *db != NO_DB).collect();
+        if dbs.contains(NO_DB) {
+            columns.insert(0, NO_DB);
+        }
+        columns
+    }
+
+    /// The frontend × database table, with a mark for every combination that exists.
+    pub fn matrix_table(&self) -> String {
+        let columns = self.databases();
+        let label_width = self.matrix.keys().map(|f| f.len()).max().unwrap_or(0).max(8);
+
+        let mut out = format!("{:<width$}", "", width = label_width);
+        for db in &columns {
+            out.push_str(&format!("  {}", db));
+        }
+        out.push('\n');
+        for (frontend, row) in &self.matrix {
+            let mut line = format!("{:<width$}", frontend, width = label_width);
+            for db in &columns {
+                let mark = if row.contains_key(*db) { "✓" } else { "-" };
+                line.push_str(&format!("  {:^width$}", mark, width = db.len()));
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn print(&self) {
+        for entry in &self.templates {
+            let m = &entry.manifest;
+            let version = m.version.as_deref().map(|v| format!(" {}", v)).unwrap_or_default();
+            println!("{}{}  {}", m.id.bold().cyan(), version, m.description);
+            let selector = match &m.db {
+                Some(db) => format!("--frontend {} --db {}", m.frontend, db),
+                None => format!("--frontend {}", m.frontend),
+            };
+            println!("   {:<8} {} or --template {}", "use".dimmed(), selector, m.id);
+            match entry.source.kind {
+                "embedded" => println!("   {:<8} built-in", "source".dimmed()),
+                kind => println!("   {:<8} {} ({})", "source".dimmed(), entry.source.location, kind),
+            }
+            for (name, addon) in &m.addons {
+                println!(
+                    "   {:<8} --addon {} ({}): {}",
+                    "add-on".dimmed(),
+                    name,
+                    if addon.default { "on by default" } else { "off by default" },
+                    addon.description
+                );
+            }
+            if !m.env.is_empty() {
+                println!("   {:<8} {}", "env".dimmed(), m.env.join(", "));
+            }
+            println!();
+        }
+
+        println!("{}", "Frontend × database:".bold());
+        print!("{}", self.matrix_table());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::source::DirectorySource;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn template(id: &str, frontend: &str, db: Option<&str>) -> (TemplateManifest, TemplateLocation) {
+        let manifest = TemplateManifest::parse(&format!(
+            "id = \"{}\"\ndescription = \"\"\nfrontend = \"{}\"\n{}",
+            id,
+            frontend,
+            db.map(|db| format!("db = \"{}\"\n", db)).unwrap_or_default()
+        ))
+        .unwrap();
+        let location = TemplateLocation {
+            source: Arc::new(DirectorySource::new("/templates")),
+            path: PathBuf::from(id),
+        };
+        (manifest, location)
+    }
+
+    #[test]
+    fn test_matrix_marks_existing_combinations() {
+        let catalog = Catalog::new(vec![
+            template("static-htmx", "htmx", None),
+            template("postgres-htmx", "htmx", Some("postgres")),
+            template("static-dioxus", "dioxus", None),
+        ]);
+
+        assert_eq!(catalog.matrix["htmx"]["postgres"], "postgres-htmx");
+        assert_eq!(
+            catalog.matrix_table(),
+            "          none  postgres\n\
+             dioxus     ✓       -\n\
+             htmx       ✓       ✓\n"
+        );
+    }
+
+    #[test]
+    fn test_json_includes_source_and_manifest_fields() {
+        let catalog = Catalog::new(vec![template("postgres-htmx", "htmx", Some("postgres"))]);
+        let json = serde_json::to_value(&catalog).unwrap();
+
+        let entry = &json["templates"][0];
+        assert_eq!(entry["id"], "postgres-htmx");
+        assert_eq!(entry["db"], "postgres");
+        assert_eq!(entry["source"]["kind"], "directory");
+        assert_eq!(json["matrix"]["htmx"]["postgres"], "postgres-htmx");
+    }
+}
@@ -0,0 +1,369 @@
+use super::vcs::Vcs;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+
+/// Name of the per-user configuration file inside [`user_config_dir`].
+pub const USER_CONFIG_FILE: &str = "config.toml";
+/// Name of the repository configuration file, found by walking up from the current directory.
+pub const REPO_CONFIG_FILE: &str = ".trestle.toml";
+
+/// Every key `trestle config` knows, with a short description.
+pub const KEYS: &[(&str, &str)] = &[
+    ("frontend", "default --frontend"),
+    ("db", "default --db (`none` for static sites)"),
+    ("addons.<name>", "turn an add-on on or off by default (true/false)"),
+    ("author", "author written into generated projects"),
+    ("license", "SPDX license expression for generated projects"),
+    ("vcs", "default --vcs (git or none)"),
+    ("template_paths", "extra template directories, comma separated"),
+];
+
+/// Default choices for `trestle new`, read from `config.toml` and `.trestle.toml`.
+///
+/// Every field is optional; unset fields fall through to the next, less specific, file.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub frontend: Option<String>,
+    /// `none` selects a static site.
+    pub db: Option<String>,
+    #[serde(default)]
+    pub addons: BTreeMap<String, bool>,
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub vcs: Option<String>,
+    /// Directories holding templates, searched after the user template directory.
+    #[serde(default)]
+    pub template_paths: Vec<PathBuf>,
+}
+
+/// A config file that was found and parsed.
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    pub config: Config,
+}
+
+impl ConfigFile {
+    /// Whether this is a `.trestle.toml` rather than the user config.
+    pub fn is_repo(&self) -> bool {
+        self.path.file_name().is_some_and(|n| n == REPO_CONFIG_FILE)
+    }
+}
+
+/// The configuration in effect: the user config, then the repo config, most specific last.
+#[derive(Debug, Default, Clone)]
+pub struct Settings {
+    pub files: Vec<ConfigFile>,
+}
+
+/// The per-user configuration directory: `$XDG_CONFIG_HOME/trestle`, falling back to
+/// `~/.config/trestle`.
+pub fn user_config_dir() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config.join("trestle"))
+}
+
+/// The nearest `.trestle.toml` in `dir` or one of its ancestors.
+pub fn find_repo_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(REPO_CONFIG_FILE))
+        .find(|p| p.is_file())
+}
+
+impl Config {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config: {}", path.display()))?;
+        let mut config: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config: {}", path.display()))?;
+        if let Some(vcs) = &config.vcs {
+            Vcs::from_str(vcs, true).map_err(|_| {
+                anyhow::anyhow!("Invalid vcs '{}' in {} (use git or none)", vcs, path.display())
+            })?;
+        }
+        // Template paths are relative to the file that names them.
+        let base = path.parent().unwrap_or(Path::new("."));
+        for template_path in &mut config.template_paths {
+            *template_path = expand_path(base, template_path);
+        }
+        Ok(config)
+    }
+}
+
+impl Settings {
+    /// Read the user config and the nearest repo config above `cwd`. Missing files are
+    /// simply skipped.
+    pub fn load(cwd: &Path) -> Result<Self> {
+        let mut files = Vec::new();
+        if let Some(path) = user_config_dir().map(|d| d.join(USER_CONFIG_FILE)) {
+            if path.is_file() {
+                files.push(ConfigFile {
+                    config: Config::read(&path)?,
+                    path,
+                });
+            }
+        }
+        if let Some(path) = find_repo_config(cwd) {
+            files.push(ConfigFile {
+                config: Config::read(&path)?,
+                path,
+            });
+        }
+        Ok(Self { files })
+    }
+
+    /// Settings for the current directory.
+    pub fn load_current() -> Result<Self> {
+        Self::load(&std::env::current_dir()?)
+    }
+
+    /// The most specific value of a field, if any file sets it.
+    fn pick<T>(&self, field: impl Fn(&Config) -> Option<T>) -> Option<T> {
+        self.files.iter().rev().find_map(|f| field(&f.config))
+    }
+
+    pub fn frontend(&self) -> Option<String> {
+        self.pick(|c| c.frontend.clone())
+    }
+
+    /// The default database: `Some(None)` when a file asks for a static site.
+    pub fn db(&self) -> Option<Option<String>> {
+        self.pick(|c| c.db.clone()).map(|db| (db != "none").then_some(db))
+    }
+
+    pub fn author(&self) -> Option<String> {
+        self.pick(|c| c.author.clone())
+    }
+
+    pub fn license(&self) -> Option<String> {
+        self.pick(|c| c.license.clone())
+    }
+
+    pub fn vcs(&self) -> Option<Vcs> {
+        self.pick(|c| c.vcs.as_deref().and_then(|v| Vcs::from_str(v, true).ok()))
+    }
+
+    /// Add-on defaults, with more specific files overriding per add-on.
+    pub fn addons(&self) -> BTreeMap<String, bool> {
+        let mut addons = BTreeMap::new();
+        for file in &self.files {
+            addons.extend(file.config.addons.clone());
+        }
+        addons
+    }
+
+    /// Template directories from every file, most specific first.
+    pub fn template_paths(&self) -> Vec<PathBuf> {
+        self.template_paths_where(|_| true)
+    }
+
+    /// Template directories from the user config.
+    pub fn user_template_paths(&self) -> Vec<PathBuf> {
+        self.template_paths_where(|f| !f.is_repo())
+    }
+
+    /// Template directories from the repo config, which comes with whatever was checked out.
+    pub fn repo_template_paths(&self) -> Vec<PathBuf> {
+        self.template_paths_where(ConfigFile::is_repo)
+    }
+
+    fn template_paths_where(&self, keep: impl Fn(&ConfigFile) -> bool) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .rev()
+            .filter(|f| keep(f))
+            .flat_map(|f| f.config.template_paths.clone())
+            .collect()
+    }
+
+    /// Every value in effect as `(key, value, file)`, sorted by key.
+    pub fn list(&self) -> Vec<(String, String, PathBuf)> {
+        let mut values: BTreeMap<String, (String, PathBuf)> = BTreeMap::new();
+        for file in &self.files {
+            let c = &file.config;
+            let scalars = [
+                ("frontend", &c.frontend),
+                ("db", &c.db),
+                ("author", &c.author),
+                ("license", &c.license),
+                ("vcs", &c.vcs),
+            ];
+            for (key, v) in scalars {
+                if let Some(v) = v {
+                    values.insert(key.to_string(), (v.clone(), file.path.clone()));
+                }
+            }
+            for (name, on) in &c.addons {
+                values.insert(format!("addons.{}", name), (on.to_string(), file.path.clone()));
+            }
+        }
+        let paths: Vec<String> = self.template_paths().iter().map(|p| p.display().to_string()).collect();
+        if !paths.is_empty() {
+            let origin = self
+                .files
+                .iter()
+                .rev()
+                .find(|f| !f.config.template_paths.is_empty())
+                .map(|f| f.path.clone())
+                .unwrap_or_default();
+            values.insert("template_paths".to_string(), (paths.join(","), origin));
+        }
+        values.into_iter().map(|(k, (v, path))| (k, v, path)).collect()
+    }
+
+    /// The value in effect for `key`, and the file it comes from.
+    pub fn get(&self, key: &str) -> Result<Option<(String, PathBuf)>> {
+        check_key(key)?;
+        Ok(self
+            .list()
+            .into_iter()
+            .find(|(k, _, _)| k == key)
+            .map(|(_, v, path)| (v, path)))
+    }
+}
+
+/// Set `key` to `raw` in the config file at `path`, creating it if needed. Comments and
+/// other keys in the file are kept.
+pub fn set(path: &Path, key: &str, raw: &str) -> Result<()> {
+    check_key(key)?;
+    let content = if path.is_file() {
+        fs::read_to_string(path).with_context(|| format!("Failed to read config: {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut doc: DocumentMut = content
+        .parse()
+        .with_context(|| format!("Failed to parse config: {}", path.display()))?;
+
+    if let Some(name) = key.strip_prefix("addons.") {
+        let on: bool = raw
+            .parse()
+            .map_err(|_| anyhow::anyhow!("addons.{} must be true or false", name))?;
+        let addons = doc
+            .entry("addons")
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| anyhow::anyhow!("'addons' in {} is not a table", path.display()))?;
+        addons[name] = value(on);
+    } else if key == "template_paths" {
+        let paths: Array = raw.split(',').map(str::trim).filter(|p| !p.is_empty()).collect();
+        doc[key] = value(paths);
+    } else {
+        if key == "vcs" {
+            Vcs::from_str(raw, true).map_err(|_| anyhow::anyhow!("vcs must be git or none"))?;
+        }
+        doc[key] = value(raw);
+    }
+
+    let updated = doc.to_string();
+    // Refuse to write anything the loader would reject.
+    toml::from_str::<Config>(&updated).context("The new value is not valid")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, updated).with_context(|| format!("Failed to write config: {}", path.display()))
+}
+
+fn check_key(key: &str) -> Result<()> {
+    let known = KEYS.iter().any(|(k, _)| match k.strip_suffix("<name>") {
+        Some(prefix) => key.strip_prefix(prefix).is_some_and(|name| !name.is_empty()),
+        None => *k == key,
+    });
+    if !known {
+        let keys: Vec<&str> = KEYS.iter().map(|(k, _)| *k).collect();
+        anyhow::bail!("Unknown config key '{}' (known keys: {})", key, keys.join(", "));
+    }
+    Ok(())
+}
+
+/// Resolve `~/` and relative paths.
+fn expand_path(base: &Path, path: &Path) -> PathBuf {
+    if let Ok(rest) = path.strip_prefix("~") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    base.join(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn test_repo_config_overrides_user_config() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        dir.child("user.toml")
+            .write_str("frontend = \"htmx\"\ndb = \"postgres\"\nauthor = \"Ada\"\ntemplate_paths = [\"shared\"]\n\n[addons]\ntailwind = false\ndocker = true\n")
+            .unwrap();
+        dir.child("repo/.trestle.toml")
+            .write_str("db = \"none\"\nvcs = \"git\"\n\n[addons]\ntailwind = true\n")
+            .unwrap();
+        dir.child("repo/services/api").create_dir_all().unwrap();
+
+        let repo_config = find_repo_config(&dir.path().join("repo/services/api")).unwrap();
+        assert_eq!(repo_config, dir.path().join("repo/.trestle.toml"));
+        let settings = Settings {
+            files: vec![
+                ConfigFile {
+                    path: dir.path().join("user.toml"),
+                    config: Config::read(&dir.path().join("user.toml")).unwrap(),
+                },
+                ConfigFile {
+                    config: Config::read(&repo_config).unwrap(),
+                    path: repo_config.clone(),
+                },
+            ],
+        };
+
+        assert_eq!(settings.frontend().as_deref(), Some("htmx"));
+        assert_eq!(settings.db(), Some(None));
+        assert_eq!(settings.author().as_deref(), Some("Ada"));
+        assert_eq!(settings.vcs(), Some(Vcs::Git));
+        assert_eq!(
+            settings.addons(),
+            BTreeMap::from([("docker".to_string(), true), ("tailwind".to_string(), true)])
+        );
+        assert_eq!(settings.template_paths(), vec![dir.path().join("shared")]);
+        assert_eq!(settings.user_template_paths(), vec![dir.path().join("shared")]);
+        assert!(settings.repo_template_paths().is_empty());
+        assert_eq!(
+            settings.get("db").unwrap(),
+            Some(("none".to_string(), repo_config))
+        );
+        assert!(settings.get("colour").is_err());
+    }
+
+    #[test]
+    fn test_set_keeps_comments_and_validates() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("trestle/config.toml");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "# my defaults\nfrontend = \"dioxus\"\n").unwrap();
+
+        set(&path, "frontend", "htmx").unwrap();
+        set(&path, "addons.tailwind", "false").unwrap();
+        set(&path, "template_paths", "~/templates, ./more").unwrap();
+        assert!(set(&path, "vcs", "svn").is_err());
+        assert!(set(&path, "addons.tailwind", "maybe").is_err());
+        assert!(set(&path, "colour", "red").is_err());
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# my defaults\nfrontend = \"htmx\"\n"), "{}", content);
+        let config = Config::read(&path).unwrap();
+        assert!(!config.addons["tailwind"]);
+        assert_eq!(config.template_paths.len(), 2);
+        assert_eq!(config.template_paths[1], path.parent().unwrap().join("./more"));
+    }
+}
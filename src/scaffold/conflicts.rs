@@ -0,0 +1,306 @@
+use super::layers::{merge_lines_into_existing, RenderedProject};
+use super::lockfile::{BASE_FILE, LOCK_FILE};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use colored::*;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Suffix of the side file written next to a conflicting file with [`ConflictStrategy::Side`].
+pub const SIDE_SUFFIX: &str = ".trestle-new";
+
+/// What to do when a generated file already exists with different contents.
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
+#[clap(rename_all = "kebab_case")]
+pub enum ConflictStrategy {
+    /// Keep the existing file
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Keep the existing file and write the generated one next to it as `<file>.trestle-new`
+    Side,
+    /// Ask for every conflicting file
+    Ask,
+}
+
+impl ConflictStrategy {
+    /// `Ask` when a user can answer, `Skip` otherwise.
+    pub fn default_for_terminal() -> Self {
+        if io::stdin().is_terminal() {
+            Self::Ask
+        } else {
+            Self::Skip
+        }
+    }
+}
+
+/// What happened to each generated file when writing into an existing directory.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WriteSummary {
+    pub created: Vec<PathBuf>,
+    pub overwritten: Vec<PathBuf>,
+    /// Existing `.gitignore`/`.env.example` files the generated lines were merged into.
+    pub merged: Vec<PathBuf>,
+    pub skipped: Vec<PathBuf>,
+    /// Conflicting files whose generated version was written as a `.trestle-new` side file.
+    pub conflicted: Vec<PathBuf>,
+    /// Files that already existed with identical contents.
+    pub unchanged: Vec<PathBuf>,
+}
+
+impl WriteSummary {
+    pub fn print(&self) {
+        let sections = [
+            ("Created", &self.created, "+".green()),
+            ("Overwritten", &self.overwritten, "~".yellow()),
+            ("Merged", &self.merged, "~".cyan()),
+            ("Skipped (kept existing)", &self.skipped, "-".dimmed()),
+            ("Conflicted (see *.trestle-new)", &self.conflicted, "!".red()),
+        ];
+        for (title, paths, marker) in sections {
+            if paths.is_empty() {
+                continue;
+            }
+            println!("\n{} ({}):", title.bold(), paths.len());
+            for path in paths {
+                println!("   {} {}", marker, path.display());
+            }
+        }
+        if !self.unchanged.is_empty() {
+            println!("\n{} file(s) already up to date.", self.unchanged.len());
+        }
+    }
+}
+
+/// Write `project` into an existing directory, resolving conflicts with `strategy`.
+///
+/// Generated `.gitignore` and `.env.example` lines are merged into existing files the same
+/// way layers merge them, keeping the user's values, and the lockfile is always rewritten
+/// so it describes this generation. The stored render is only written when every other
+/// file was written as rendered: a kept user file must not look generated to
+/// `trestle update`, which then goes by the recorded hashes instead.
+pub fn write_into_existing(
+    project: &RenderedProject,
+    target_dir: &Path,
+    strategy: ConflictStrategy,
+) -> Result<WriteSummary> {
+    let mut summary = WriteSummary::default();
+    let mut strategy = strategy;
+
+    for (path, file) in &project.files {
+        if path == Path::new(BASE_FILE) {
+            continue;
+        }
+        let target_path = target_dir.join(path);
+        if !target_path.exists() {
+            write_file(&target_path, &file.contents)?;
+            summary.created.push(path.clone());
+            continue;
+        }
+
+        let existing = fs::read(&target_path)
+            .with_context(|| format!("Failed to read file: {}", target_path.display()))?;
+        if existing == file.contents {
+            summary.unchanged.push(path.clone());
+            continue;
+        }
+        if path == Path::new(LOCK_FILE) {
+            write_file(&target_path, &file.contents)?;
+            summary.overwritten.push(path.clone());
+            continue;
+        }
+        if let Some(merged) = merge_existing(path, &existing, &file.contents) {
+            if merged == existing {
+                summary.unchanged.push(path.clone());
+            } else {
+                write_file(&target_path, &merged)?;
+                summary.merged.push(path.clone());
+            }
+            continue;
+        }
+
+        let choice = match strategy {
+            ConflictStrategy::Ask => {
+                let (choice, remember) = ask(path)?;
+                if remember {
+                    strategy = choice;
+                }
+                choice
+            }
+            other => other,
+        };
+
+        match choice {
+            ConflictStrategy::Overwrite => {
+                write_file(&target_path, &file.contents)?;
+                summary.overwritten.push(path.clone());
+            }
+            ConflictStrategy::Side => {
+                let mut side = target_path.into_os_string();
+                side.push(SIDE_SUFFIX);
+                write_file(Path::new(&side), &file.contents)?;
+                summary.conflicted.push(path.clone());
+            }
+            ConflictStrategy::Skip | ConflictStrategy::Ask => {
+                summary.skipped.push(path.clone());
+            }
+        }
+    }
+
+    if let Some(base) = project.files.get(Path::new(BASE_FILE)) {
+        let path = PathBuf::from(BASE_FILE);
+        let target_path = target_dir.join(&path);
+        if !summary.skipped.is_empty() || !summary.conflicted.is_empty() {
+            if target_path.exists() {
+                fs::remove_file(&target_path)
+                    .with_context(|| format!("Failed to remove file: {}", target_path.display()))?;
+            }
+        } else if !target_path.exists() {
+            write_file(&target_path, &base.contents)?;
+            summary.created.push(path);
+        } else if fs::read(&target_path).ok().as_deref() == Some(base.contents.as_slice()) {
+            summary.unchanged.push(path);
+        } else {
+            write_file(&target_path, &base.contents)?;
+            summary.overwritten.push(path);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// The generated lines merged into an existing line-oriented file, or `None` for files
+/// that are not merged.
+pub(super) fn merge_existing(path: &Path, existing: &[u8], generated: &[u8]) -> Option<Vec<u8>> {
+    if !matches!(path.file_name().and_then(|n| n.to_str()), Some(".gitignore" | ".env.example")) {
+        return None;
+    }
+    let existing = std::str::from_utf8(existing).ok()?;
+    let generated = std::str::from_utf8(generated).ok()?;
+    Some(merge_lines_into_existing(existing, generated).into_bytes())
+}
+
+pub(super) fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("Failed to write file: {}", path.display()))
+}
+
+/// Prompt for a single conflict. Returns the choice and whether it applies to all remaining files.
+fn ask(path: &Path) -> Result<(ConflictStrategy, bool)> {
+    let stdin = io::stdin();
+    loop {
+        print!(
+            "{} '{}' already exists. [s]kip, [o]verwrite, write [n]ew side file (uppercase = all remaining) [s]: ",
+            "?".yellow(),
+            path.display()
+        );
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            // stdin closed: fall back to keeping the existing file.
+            return Ok((ConflictStrategy::Skip, true));
+        }
+        let answer = answer.trim();
+        let remember = answer.chars().next().is_some_and(|c| c.is_ascii_uppercase());
+        match answer.to_ascii_lowercase().as_str() {
+            "" | "s" | "skip" => return Ok((ConflictStrategy::Skip, remember)),
+            "o" | "overwrite" => return Ok((ConflictStrategy::Overwrite, remember)),
+            "n" | "new" => return Ok((ConflictStrategy::Side, remember)),
+            _ => println!("Please answer s, o or n."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing_dir() -> assert_fs::TempDir {
+        let dir = assert_fs::TempDir::new().unwrap();
+        fs::write(dir.path().join("README.md"), "# Mine\n").unwrap();
+        fs::write(dir.path().join("LICENSE"), "MIT\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_stored_render_is_only_written_when_nothing_was_kept() {
+        let dir = existing_dir();
+        fs::create_dir(dir.path().join(".trestle")).unwrap();
+        fs::write(dir.path().join(BASE_FILE), "stale").unwrap();
+        let project = RenderedProject::from_files(&[("README.md", "# generated\n"), (BASE_FILE, "render")]);
+
+        let summary = write_into_existing(&project, dir.path(), ConflictStrategy::Skip).unwrap();
+        assert_eq!(summary.skipped, vec![PathBuf::from("README.md")]);
+        assert!(!dir.path().join(BASE_FILE).exists());
+
+        let summary = write_into_existing(&project, dir.path(), ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(summary.created, vec![PathBuf::from(BASE_FILE)]);
+        assert_eq!(fs::read_to_string(dir.path().join(BASE_FILE)).unwrap(), "render");
+    }
+
+    #[test]
+    fn test_skip_keeps_existing_files() {
+        let dir = existing_dir();
+        let project = RenderedProject::from_files(&[("README.md", "# Generated\n"), ("LICENSE", "MIT\n"), ("src/main.rs", "fn main() {}\n")]);
+
+        let summary = write_into_existing(&project, dir.path(), ConflictStrategy::Skip).unwrap();
+        assert_eq!(summary.created, vec![PathBuf::from("src/main.rs")]);
+        assert_eq!(summary.skipped, vec![PathBuf::from("README.md")]);
+        assert_eq!(summary.unchanged, vec![PathBuf::from("LICENSE")]);
+        assert_eq!(fs::read_to_string(dir.path().join("README.md")).unwrap(), "# Mine\n");
+    }
+
+    #[test]
+    fn test_overwrite_and_side_strategies() {
+        let dir = existing_dir();
+        let project = RenderedProject::from_files(&[("README.md", "# Generated\n")]);
+        let summary = write_into_existing(&project, dir.path(), ConflictStrategy::Side).unwrap();
+        assert_eq!(summary.conflicted, vec![PathBuf::from("README.md")]);
+        assert_eq!(fs::read_to_string(dir.path().join("README.md")).unwrap(), "# Mine\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("README.md.trestle-new")).unwrap(),
+            "# Generated\n"
+        );
+
+        let summary = write_into_existing(&project, dir.path(), ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(summary.overwritten, vec![PathBuf::from("README.md")]);
+        assert_eq!(fs::read_to_string(dir.path().join("README.md")).unwrap(), "# Generated\n");
+    }
+
+    #[test]
+    fn test_gitignore_env_and_lockfile_are_merged_or_rewritten() {
+        let dir = existing_dir();
+        fs::write(dir.path().join(".gitignore"), "node_modules/\ntarget/\n").unwrap();
+        fs::write(dir.path().join(".env.example"), "PORT=8080\n").unwrap();
+        fs::create_dir(dir.path().join(".trestle")).unwrap();
+        fs::write(dir.path().join(LOCK_FILE), "stale").unwrap();
+        let project = RenderedProject::from_files(&[
+            (".gitignore", "target/\n.env\n"),
+            (".env.example", "PORT=3000\nDATABASE_URL=\"postgres://\"\n"),
+            (LOCK_FILE, "fresh"),
+        ]);
+
+        let summary = write_into_existing(&project, dir.path(), ConflictStrategy::Skip).unwrap();
+        assert_eq!(summary.merged, vec![PathBuf::from(".env.example"), PathBuf::from(".gitignore")]);
+        assert_eq!(summary.overwritten, vec![PathBuf::from(LOCK_FILE)]);
+        assert!(summary.skipped.is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.path().join(".gitignore")).unwrap(),
+            "node_modules/\ntarget/\n.env\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join(".env.example")).unwrap(),
+            "PORT=8080\nDATABASE_URL=\"postgres://\"\n"
+        );
+        assert_eq!(fs::read_to_string(dir.path().join(LOCK_FILE)).unwrap(), "fresh");
+
+        let summary = write_into_existing(&project, dir.path(), ConflictStrategy::Skip).unwrap();
+        assert!(summary.merged.is_empty());
+        assert_eq!(summary.unchanged.len(), 3);
+    }
+}
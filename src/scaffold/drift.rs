@@ -0,0 +1,229 @@
+use super::lockfile::LOCK_FILE;
+use super::update::Base;
+use anyhow::{Context, Result};
+use colored::*;
+use diffy::{DiffOptions, Line};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file that differs from the template.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ModifiedFile {
+    pub path: PathBuf,
+    /// Unified diff from the template version to the project version; `None` when only
+    /// the recorded hash is known.
+    pub patch: Option<String>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// How far a project has moved from the template it was generated from.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Drift {
+    pub modified: Vec<ModifiedFile>,
+    /// Files in the project that the template does not generate.
+    pub added: Vec<PathBuf>,
+    /// Generated files missing from the project.
+    pub removed: Vec<PathBuf>,
+}
+
+impl Drift {
+    /// Compare the files of `project_dir` with what was generated.
+    ///
+    /// Added files are found by walking the project, honouring its `.gitignore`, so build
+    /// output is not reported. `.git`, `.trestle` and `target` are always skipped.
+    pub fn compute(project_dir: &Path, base: &Base) -> Result<Self> {
+        let expected = base.paths();
+
+        let mut drift = Self::default();
+        for path in &expected {
+            if path.starts_with(".trestle") {
+                continue;
+            }
+            let target = project_dir.join(path);
+            if !target.is_file() {
+                drift.removed.push(path.clone());
+                continue;
+            }
+            let ours = fs::read(&target)
+                .with_context(|| format!("Failed to read file: {}", target.display()))?;
+            let modified = match (base, base.contents(path, Some(&ours))) {
+                (Base::Rendered(_), Some(generated)) => modified(path, &generated, &ours),
+                (_, Some(_)) => None,
+                // Only the hash is known, and it no longer matches.
+                (_, None) => Some(ModifiedFile {
+                    path: path.clone(),
+                    patch: None,
+                    insertions: 0,
+                    deletions: 0,
+                }),
+            };
+            drift.modified.extend(modified);
+        }
+
+        drift.added = project_files(project_dir)?
+            .into_iter()
+            .filter(|path| !expected.contains(path) && path != Path::new(LOCK_FILE))
+            .collect();
+        Ok(drift)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// The unified diff of every modified file, followed by the added and removed files.
+    pub fn print(&self) {
+        for file in &self.modified {
+            match &file.patch {
+                Some(patch) => super::preview::print_diff(patch),
+                None => println!("{} {}", "Modified:".bold(), file.path.display()),
+            }
+        }
+        self.print_added_removed();
+    }
+
+    /// A `git diff --stat` style summary.
+    pub fn print_stat(&self) {
+        let width = self
+            .modified
+            .iter()
+            .map(|f| f.path.as_os_str().len())
+            .max()
+            .unwrap_or(0);
+        let (mut insertions, mut deletions) = (0, 0);
+        for file in &self.modified {
+            insertions += file.insertions;
+            deletions += file.deletions;
+            println!(
+                " {:<width$} | {:>4} {}{}",
+                file.path.display(),
+                file.insertions + file.deletions,
+                "+".repeat(file.insertions.min(40)).green(),
+                "-".repeat(file.deletions.min(40)).red(),
+                width = width
+            );
+        }
+        println!(
+            " {} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)",
+            self.modified.len(),
+            insertions,
+            deletions
+        );
+        self.print_added_removed();
+    }
+
+    fn print_added_removed(&self) {
+        let sections = [
+            ("Added (not in template)", &self.added, "+".green()),
+            ("Removed (generated, now missing)", &self.removed, "-".red()),
+        ];
+        for (title, paths, marker) in sections {
+            if paths.is_empty() {
+                continue;
+            }
+            println!("\n{} ({}):", title.bold(), paths.len());
+            for path in paths {
+                println!("   {} {}", marker, path.display());
+            }
+        }
+    }
+}
+
+fn modified(path: &Path, template: &[u8], ours: &[u8]) -> Option<ModifiedFile> {
+    if template == ours {
+        return None;
+    }
+    let mut file = ModifiedFile {
+        path: path.to_path_buf(),
+        patch: None,
+        insertions: 0,
+        deletions: 0,
+    };
+    match (std::str::from_utf8(template), std::str::from_utf8(ours)) {
+        (Ok(template), Ok(ours)) => {
+            let patch = DiffOptions::new()
+                .set_original_filename(format!("a/{}", path.display()))
+                .set_modified_filename(format!("b/{}", path.display()))
+                .create_patch(template, ours);
+            for line in patch.hunks().iter().flat_map(|h| h.lines()) {
+                match line {
+                    Line::Insert(_) => file.insertions += 1,
+                    Line::Delete(_) => file.deletions += 1,
+                    Line::Context(_) => {}
+                }
+            }
+            file.patch = Some(patch.to_string());
+        }
+        _ => {
+            file.patch = Some(format!(
+                "Binary files a/{} and b/{} differ\n",
+                path.display(),
+                path.display()
+            ))
+        }
+    }
+    Some(file)
+}
+
+/// Every file below `dir` that is not ignored, relative to `dir`.
+fn project_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let walker = ignore::WalkBuilder::new(dir)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some(".git" | ".trestle" | "target")))
+        .build();
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            files.push(entry.path().strip_prefix(dir)?.to_path_buf());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::layers::RenderedProject;
+    use crate::scaffold::pack::sha256_hex;
+    use assert_fs::prelude::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_drift_lists_modified_added_and_removed_files() {
+        let template = RenderedProject::from_files(&[
+            ("src/main.rs", "fn main() {}\n"),
+            ("README.md", "# app\n"),
+            (".gitignore", "/target\n"),
+        ]);
+
+        let project = assert_fs::TempDir::new().unwrap();
+        project.child("src/main.rs").write_str("fn main() {\n    run();\n}\n").unwrap();
+        project.child(".gitignore").write_str("/target\n*.log\n").unwrap();
+        project.child("server.log").write_str("").unwrap();
+        project.child("src/extra.rs").write_str("").unwrap();
+        project.child("target/debug/app").write_str("binary").unwrap();
+        project.child(".trestle/project.toml").write_str("").unwrap();
+
+        let drift = Drift::compute(project.path(), &Base::Rendered(&template)).unwrap();
+        assert_eq!(drift.modified.len(), 2);
+        let main = &drift.modified[1];
+        assert_eq!(main.path, PathBuf::from("src/main.rs"));
+        assert_eq!((main.insertions, main.deletions), (3, 1));
+        assert!(main.patch.as_ref().unwrap().contains("--- a/src/main.rs\n+++ b/src/main.rs\n"));
+        assert_eq!(drift.added, vec![PathBuf::from("src/extra.rs")]);
+        assert_eq!(drift.removed, vec![PathBuf::from("README.md")]);
+
+        let hashes = BTreeMap::from([
+            ("src/main.rs".to_string(), sha256_hex(b"fn main() {}\n")),
+            (".gitignore".to_string(), sha256_hex(b"/target\n")),
+        ]);
+        let drift = Drift::compute(project.path(), &Base::Hashes(&hashes)).unwrap();
+        assert_eq!(drift.modified[1].path, PathBuf::from("src/main.rs"));
+        assert_eq!(drift.modified[1].patch, None);
+        assert!(drift.removed.is_empty());
+    }
+}
@@ -0,0 +1,635 @@
+use super::lockfile::ProjectLock;
+use super::render::RenderContext;
+use super::validate::RUST_KEYWORDS;
+use super::TEMPLATES;
+use anyhow::{Context, Result};
+use colored::*;
+use heck::{ToSnakeCase, ToTitleCase, ToUpperCamelCase};
+use minijinja::Value;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The column types `trestle generate resource` understands, with the aliases accepted on
+/// the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    String,
+    Text,
+    Bool,
+    Int,
+    BigInt,
+    Float,
+}
+
+const FIELD_KINDS: &[(&str, FieldKind)] = &[
+    ("string", FieldKind::String),
+    ("text", FieldKind::Text),
+    ("bool", FieldKind::Bool),
+    ("boolean", FieldKind::Bool),
+    ("int", FieldKind::Int),
+    ("integer", FieldKind::Int),
+    ("i32", FieldKind::Int),
+    ("bigint", FieldKind::BigInt),
+    ("i64", FieldKind::BigInt),
+    ("float", FieldKind::Float),
+    ("double", FieldKind::Float),
+    ("f64", FieldKind::Float),
+];
+
+impl FieldKind {
+    fn parse(kind: &str) -> Option<Self> {
+        FIELD_KINDS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(kind))
+            .map(|(_, kind)| *kind)
+    }
+
+    /// The canonical name, as the generator templates test it.
+    fn name(self) -> &'static str {
+        match self {
+            FieldKind::String => "string",
+            FieldKind::Text => "text",
+            FieldKind::Bool => "bool",
+            FieldKind::Int => "int",
+            FieldKind::BigInt => "bigint",
+            FieldKind::Float => "float",
+        }
+    }
+
+    fn rust_type(self) -> &'static str {
+        match self {
+            FieldKind::String | FieldKind::Text => "String",
+            FieldKind::Bool => "bool",
+            FieldKind::Int => "i32",
+            FieldKind::BigInt => "i64",
+            FieldKind::Float => "f64",
+        }
+    }
+
+    fn sql_type(self, db: &str) -> &'static str {
+        match (self, db) {
+            (FieldKind::String, "mysql") => "VARCHAR(255)",
+            (FieldKind::String | FieldKind::Text, _) => "TEXT",
+            (FieldKind::Bool, _) => "BOOLEAN",
+            (FieldKind::Int, _) => "INTEGER",
+            (FieldKind::BigInt, _) => "BIGINT",
+            (FieldKind::Float, "mysql") => "DOUBLE",
+            (FieldKind::Float, _) => "DOUBLE PRECISION",
+        }
+    }
+}
+
+/// One `name:type` argument of `trestle generate resource`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub kind: FieldKind,
+}
+
+impl Field {
+    pub fn parse(arg: &str) -> Result<Self> {
+        let (name, kind) = arg
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Invalid field '{}': use name:type, e.g. title:string", arg))?;
+        let kind = FieldKind::parse(kind).ok_or_else(|| {
+            let kinds: Vec<&str> = FIELD_KINDS.iter().map(|(name, _)| *name).collect();
+            anyhow::anyhow!("Unknown type '{}' for field '{}' (known types: {})", kind, name, kinds.join(", "))
+        })?;
+        let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid || RUST_KEYWORDS.contains(&name) {
+            anyhow::bail!("Invalid field name '{}': use a snake_case name that is not a Rust keyword", name);
+        }
+        if name == "id" {
+            anyhow::bail!("The 'id' field is generated for every resource; leave it out");
+        }
+        Ok(Self {
+            name: name.to_string(),
+            kind,
+        })
+    }
+}
+
+/// A resource to scaffold: its names in every case the generated code needs, and its fields.
+#[derive(Debug, Clone)]
+pub struct Resource {
+    /// Type name, e.g. `BlogPost`.
+    pub pascal: String,
+    /// Variable name, e.g. `blog_post`.
+    pub snake: String,
+    /// Table, collection, module and route name, e.g. `blog_posts`.
+    pub plural: String,
+    pub fields: Vec<Field>,
+}
+
+impl Resource {
+    pub fn new(name: &str, fields: &[String]) -> Result<Self> {
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        let snake = name.to_snake_case();
+        if !valid || RUST_KEYWORDS.contains(&snake.as_str()) {
+            anyhow::bail!("Invalid resource name '{}': use a name such as Post or BlogPost", name);
+        }
+        if fields.is_empty() {
+            anyhow::bail!("Give the resource at least one field, e.g. `title:string`");
+        }
+        let fields = fields.iter().map(|f| Field::parse(f)).collect::<Result<Vec<_>>>()?;
+        for (i, field) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|f| f.name == field.name) {
+                anyhow::bail!("Field '{}' is given twice", field.name);
+            }
+        }
+        Ok(Self {
+            pascal: name.to_upper_camel_case(),
+            plural: pluralize(&snake),
+            snake,
+            fields,
+        })
+    }
+
+    /// The `resource` variable of the generator templates.
+    fn variables(&self, db: &str) -> Value {
+        #[derive(Serialize)]
+        struct FieldVars<'a> {
+            name: &'a str,
+            label: String,
+            kind: &'static str,
+            rust_type: &'static str,
+            sql_type: &'static str,
+        }
+
+        let placeholder = |i: usize| if db == "postgres" { format!("${}", i) } else { "?".to_string() };
+        let names: Vec<&str> = self.fields.iter().map(|f| f.name.as_str()).collect();
+        let assignments: Vec<String> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| format!("{} = {}", name, placeholder(i + 1)))
+            .collect();
+        let fields: Vec<FieldVars> = self
+            .fields
+            .iter()
+            .map(|f| FieldVars {
+                name: &f.name,
+                label: f.name.to_title_case(),
+                kind: f.kind.name(),
+                rust_type: f.kind.rust_type(),
+                sql_type: f.kind.sql_type(db),
+            })
+            .collect();
+
+        Value::from_serialize(serde_json::json!({
+            "pascal": self.pascal,
+            "snake": self.snake,
+            "plural": self.plural,
+            "plural_pascal": self.plural.to_upper_camel_case(),
+            "label": self.snake.replace('_', " "),
+            "plural_label": self.plural.replace('_', " "),
+            "title": self.snake.to_title_case(),
+            "plural_title": self.plural.to_title_case(),
+            "columns": names.join(", "),
+            "fields": fields,
+            "sql": {
+                "select_id": placeholder(1),
+                "insert_values": (1..=names.len()).map(placeholder).collect::<Vec<_>>().join(", "),
+                "assignments": assignments.join(", "),
+                "update_id": placeholder(names.len() + 1),
+            },
+        }))
+    }
+}
+
+/// `post` → `posts`, `category` → `categories`, `box` → `boxes`, `blog_post` → `blog_posts`.
+fn pluralize(snake: &str) -> String {
+    let consonant_y = snake.len() > 1
+        && snake.ends_with('y')
+        && !matches!(snake.as_bytes()[snake.len() - 2], b'a' | b'e' | b'i' | b'o' | b'u');
+    if consonant_y {
+        format!("{}ies", &snake[..snake.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"].iter().any(|end| snake.ends_with(end)) {
+        format!("{}es", snake)
+    } else {
+        format!("{}s", snake)
+    }
+}
+
+/// What `trestle generate` wrote, in the order it was written.
+#[derive(Debug, Default)]
+pub struct Generated {
+    pub created: Vec<PathBuf>,
+    pub updated: Vec<PathBuf>,
+    pub next_steps: Vec<String>,
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl Generated {
+    fn create(&mut self, path: impl Into<PathBuf>, contents: String) {
+        let path = path.into();
+        self.created.push(path.clone());
+        self.files.insert(path, contents);
+    }
+
+    fn update(&mut self, path: impl Into<PathBuf>, contents: String) {
+        let path = path.into();
+        self.updated.push(path.clone());
+        self.files.insert(path, contents);
+    }
+
+    /// Write every file below `project_dir`. Nothing is written before all of them were
+    /// generated, so a failing step leaves the project untouched.
+    fn write(&self, project_dir: &Path) -> Result<()> {
+        for (path, contents) in &self.files {
+            let target = project_dir.join(path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            fs::write(&target, contents).with_context(|| format!("Failed to write {}", target.display()))?;
+        }
+        Ok(())
+    }
+
+    pub fn print(&self) {
+        for path in &self.created {
+            println!("   {} {}", "create".green(), path.display());
+        }
+        for path in &self.updated {
+            println!("   {} {}", "update".yellow(), path.display());
+        }
+        if !self.next_steps.is_empty() {
+            println!("\n{}", "Next steps:".bold());
+            for step in &self.next_steps {
+                println!("   {}", step);
+            }
+        }
+    }
+}
+
+/// Scaffold a CRUD resource into the project in `project_dir`, for the frontend and
+/// database recorded in its `.trestle/project.toml`.
+pub fn generate_resource(project_dir: &Path, name: &str, fields: &[String]) -> Result<Generated> {
+    let resource = Resource::new(name, fields)?;
+    let lock = ProjectLock::read(project_dir)?;
+    let mut context = lock.context()?;
+    let frontend = variable(&context, "frontend").unwrap_or_default();
+    let Some(db) = variable(&context, "db") else {
+        anyhow::bail!(
+            "This project has no database, so there is nowhere to store {}; generate a project with --db",
+            resource.plural
+        );
+    };
+    context.insert("resource", resource.variables(&db));
+
+    let mut generated = Generated::default();
+    match frontend.as_str() {
+        "htmx" => htmx_resource(project_dir, &context, &resource, &db, &mut generated)?,
+        "dioxus" => dioxus_resource(project_dir, &context, &resource, &mut generated)?,
+        _ => anyhow::bail!("`trestle generate resource` does not support {} projects yet", frontend),
+    }
+
+    if db == "postgres" || db == "mysql" {
+        let stem = format!("migrations/{}_create_{}", migration_version(project_dir)?, resource.plural);
+        generated.create(format!("{}.up.sql", stem), render(&context, "generators/create_table.up.sql")?);
+        generated.create(format!("{}.down.sql", stem), render(&context, "generators/create_table.down.sql")?);
+        if !runs_migrations(project_dir) {
+            generated
+                .next_steps
+                .insert(0, "sqlx migrate run   (cargo install sqlx-cli)".to_string());
+        }
+    }
+
+    generated.write(project_dir)?;
+    Ok(generated)
+}
+
+/// Add an empty, reversible migration to `migrations/` of the PostgreSQL or MySQL project
+/// in `project_dir`.
+pub fn generate_migration(project_dir: &Path, name: &str) -> Result<Generated> {
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        anyhow::bail!("Invalid migration name '{}': use a name such as add_tags_to_posts", name);
+    }
+    let name = name.to_snake_case();
+    let lock = ProjectLock::read(project_dir)?;
+    let mut context = lock.context()?;
+    let db = variable(&context, "db");
+    if !matches!(db.as_deref(), Some("postgres" | "mysql")) {
+        anyhow::bail!(
+            "`trestle generate migration` needs a PostgreSQL or MySQL project; this one uses {}",
+            db.as_deref().unwrap_or("no database")
+        );
+    }
+    context.insert(
+        "migration",
+        Value::from_serialize(serde_json::json!({
+            "name": name,
+            "label": name.replace('_', " "),
+        })),
+    );
+
+    let mut generated = Generated::default();
+    let stem = format!("migrations/{}_{}", migration_version(project_dir)?, name);
+    generated.create(format!("{}.up.sql", stem), render(&context, "generators/migration.up.sql")?);
+    generated.create(format!("{}.down.sql", stem), render(&context, "generators/migration.down.sql")?);
+    generated.next_steps.push(format!("Write the SQL in {}.up.sql and {}.down.sql", stem, stem));
+    generated.next_steps.push(if runs_migrations(project_dir) {
+        "cargo run   (applies pending migrations on startup)".to_string()
+    } else {
+        "sqlx migrate run   (cargo install sqlx-cli)".to_string()
+    });
+
+    generated.write(project_dir)?;
+    Ok(generated)
+}
+
+/// The version of a new migration in `project_dir`: the current UTC time as
+/// `YYYYMMDDHHMMSS`, or one past the newest existing migration, so versions stay unique
+/// and in order when several migrations are generated within a second.
+fn migration_version(project_dir: &Path) -> Result<u64> {
+    let now: u64 = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string().parse()?;
+    let mut existing = Vec::new();
+    if let Ok(entries) = fs::read_dir(project_dir.join("migrations")) {
+        for entry in entries {
+            existing.push(entry?.file_name().to_string_lossy().into_owned());
+        }
+    }
+    Ok(next_version(&existing, now))
+}
+
+fn next_version(existing: &[String], now: u64) -> u64 {
+    existing
+        .iter()
+        .filter_map(|name| name.split_once('_')?.0.parse::<u64>().ok())
+        .max()
+        .map_or(now, |newest| now.max(newest + 1))
+}
+
+/// Whether the project applies its migrations on startup, as the SQL templates'
+/// `create_db_pool` does with `sqlx::migrate!()`.
+fn runs_migrations(project_dir: &Path) -> bool {
+    fs::read_to_string(project_dir.join("src/db.rs")).is_ok_and(|db| db.contains("sqlx::migrate!"))
+}
+
+/// An HTMX resource: a module of handlers and views in `src/<plural>.rs` merged into the
+/// app's router, and the model next to the template's database code.
+fn htmx_resource(
+    project_dir: &Path,
+    context: &RenderContext,
+    resource: &Resource,
+    db: &str,
+    generated: &mut Generated,
+) -> Result<()> {
+    let module = PathBuf::from(format!("src/{}.rs", resource.plural));
+    ensure_new(project_dir, &module)?;
+    generated.create(&module, render(context, "generators/htmx/resource.rs")?);
+
+    // The model goes next to the database code of the template.
+    let models = PathBuf::from(if db == "firebase" { "src/firebase.rs" } else { "src/db.rs" });
+    let mut source = read(project_dir, &models)?;
+    if !source.ends_with('\n') {
+        source.push('\n');
+    }
+    source.push_str(&render(context, "generators/model.rs")?);
+    generated.update(&models, source);
+
+    let main = PathBuf::from("src/main.rs");
+    let source = register_routes(&read(project_dir, &main)?, &resource.plural)?;
+    generated.update(&main, source);
+
+    generated.next_steps.push(format!(
+        "cargo run, then open http://127.0.0.1:3000/{}",
+        resource.plural
+    ));
+    Ok(())
+}
+
+/// A Dioxus fullstack resource: the shared model, the server function declarations and
+/// the components in the library module `src/<plural>.rs`, and the server functions'
+/// implementations in the binary module `src/<plural>_server.rs`. The server functions
+/// are served by the app's existing `register_server_fns("/api")`.
+fn dioxus_resource(
+    project_dir: &Path,
+    context: &RenderContext,
+    resource: &Resource,
+    generated: &mut Generated,
+) -> Result<()> {
+    let module = PathBuf::from(format!("src/{}.rs", resource.plural));
+    let server = PathBuf::from(format!("src/{}_server.rs", resource.plural));
+    ensure_new(project_dir, &module)?;
+    ensure_new(project_dir, &server)?;
+    generated.create(&module, render(context, "generators/dioxus/resource.rs")?);
+    generated.create(&server, render(context, "generators/dioxus/server.rs")?);
+
+    let lib = PathBuf::from("src/lib.rs");
+    let source = declare_module(&read(project_dir, &lib)?, &format!("pub mod {};", resource.plural));
+    generated.update(&lib, source);
+    let main = PathBuf::from("src/main.rs");
+    let source = declare_module(&read(project_dir, &main)?, &format!("mod {}_server;", resource.plural));
+    generated.update(&main, source);
+
+    // The template has no router to add a route to, so the list goes where the user wants it.
+    generated.next_steps.push(format!(
+        "Render `{}::{}List {{}}` in your app's component in src/lib.rs",
+        resource.plural, resource.pascal
+    ));
+    generated.next_steps.push("cargo run".to_string());
+    Ok(())
+}
+
+fn ensure_new(project_dir: &Path, path: &Path) -> Result<()> {
+    if project_dir.join(path).exists() {
+        anyhow::bail!("{} already exists; is the resource generated already?", path.display());
+    }
+    Ok(())
+}
+
+/// Merge the module's router into the app right before its state is attached, and declare
+/// `mod <module>;` in `main.rs`.
+fn register_routes(main: &str, module: &str) -> Result<String> {
+    let mut lines: Vec<String> = main.lines().map(str::to_string).collect();
+    let with_state = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with(".with_state("))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Could not find `.with_state(...)` in src/main.rs to register the routes; \
+                 add `.merge({}::router())` to your router by hand",
+                module
+            )
+        })?;
+    let indent: String = lines[with_state].chars().take_while(|c| c.is_whitespace()).collect();
+    lines.insert(with_state, format!("{}.merge({}::router())", indent, module));
+
+    let main = lines.join("\n");
+    Ok(declare_module(&main, &format!("mod {};", module)))
+}
+
+/// Insert `declaration` after the last module declaration of `source`, or after its inner
+/// attributes when it declares none.
+fn declare_module(source: &str, declaration: &str) -> String {
+    let mut lines: Vec<&str> = source.lines().collect();
+    let is_module = |line: &&str| {
+        (line.starts_with("mod ") || line.starts_with("pub mod ")) && line.ends_with(';')
+    };
+    let position = lines
+        .iter()
+        .rposition(is_module)
+        .or_else(|| lines.iter().rposition(|line| line.starts_with("#![")))
+        .map_or(0, |i| i + 1);
+    lines.insert(position, declaration);
+    let mut source = lines.join("\n");
+    source.push('\n');
+    source
+}
+
+fn render(context: &RenderContext, template: &str) -> Result<String> {
+    let file = TEMPLATES
+        .get_file(template)
+        .ok_or_else(|| anyhow::anyhow!("Generator template '{}' is missing", template))?;
+    let source = file
+        .contents_utf8()
+        .ok_or_else(|| anyhow::anyhow!("Generator template '{}' is not UTF-8", template))?;
+    context.render_str(template, source)
+}
+
+fn read(project_dir: &Path, path: &Path) -> Result<String> {
+    fs::read_to_string(project_dir.join(path)).with_context(|| {
+        format!(
+            "Failed to read {}; the generated code expects the layout of a Trestle template",
+            path.display()
+        )
+    })
+}
+
+fn variable(context: &RenderContext, key: &str) -> Option<String> {
+    context.get(key)?.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(db: &str, resource: &Resource) -> RenderContext {
+        let mut context = RenderContext::new("blog");
+        context
+            .insert("db", db)
+            .insert("frontend", "htmx")
+            .insert("addons", Value::from_serialize(BTreeMap::from([("tailwind", true)])))
+            .insert("resource", resource.variables(db));
+        context
+    }
+
+    #[test]
+    fn test_resource_names_and_fields() {
+        let resource = Resource::new("BlogPost", &["title:string".to_string(), "views:i64".to_string()]).unwrap();
+        assert_eq!(resource.pascal, "BlogPost");
+        assert_eq!(resource.snake, "blog_post");
+        assert_eq!(resource.plural, "blog_posts");
+        assert_eq!(resource.fields[1].kind, FieldKind::BigInt);
+
+        assert_eq!(pluralize("category"), "categories");
+        assert_eq!(pluralize("day"), "days");
+        assert_eq!(pluralize("box"), "boxes");
+        assert_eq!(pluralize("match"), "matches");
+
+        assert!(Resource::new("Post", &[]).is_err());
+        assert!(Resource::new("fn", &["a:string".to_string()]).is_err());
+        assert!(Field::parse("title").is_err());
+        assert!(Field::parse("title:varchar").is_err());
+        assert!(Field::parse("Title:string").is_err());
+        assert!(Field::parse("type:string").is_err());
+        assert!(Field::parse("id:int").is_err());
+        assert!(Resource::new("Post", &["a:string".to_string(), "a:text".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_sql_placeholders_follow_the_database() {
+        let resource = Resource::new("Post", &["title:string".to_string(), "published:bool".to_string()]).unwrap();
+
+        let postgres = render(&context("postgres", &resource), "generators/htmx/resource.rs").unwrap();
+        assert!(postgres.contains("\"INSERT INTO posts (title, published) VALUES ($1, $2)\""), "{}", postgres);
+        assert!(postgres.contains("\"UPDATE posts SET title = $1, published = $2 WHERE id = $3\""));
+        assert!(postgres.contains("Path(id): Path<i32>"));
+
+        let mysql = render(&context("mysql", &resource), "generators/htmx/resource.rs").unwrap();
+        assert!(mysql.contains("\"INSERT INTO posts (title, published) VALUES (?, ?)\""), "{}", mysql);
+        let migration = render(&context("mysql", &resource), "generators/create_table.up.sql").unwrap();
+        assert_eq!(
+            migration,
+            "CREATE TABLE IF NOT EXISTS posts (\n    id INT PRIMARY KEY AUTO_INCREMENT,\n    title VARCHAR(255) NOT NULL,\n    published BOOLEAN NOT NULL DEFAULT FALSE\n);\n"
+        );
+
+        let mongodb = render(&context("mongodb", &resource), "generators/htmx/resource.rs").unwrap();
+        assert!(mongodb.contains("doc! { \"_id\": object_id(id)? }"));
+        assert!(!mongodb.contains("sqlx"));
+        let model = render(&context("mongodb", &resource), "generators/model.rs").unwrap();
+        assert!(model.contains("pub id: Option<mongodb::bson::oid::ObjectId>,\n    pub title: String,\n    pub published: bool,\n}"));
+
+        let firebase = render(&context("firebase", &resource), "generators/model.rs").unwrap();
+        assert!(firebase.contains("format!(\"{}/posts/{}.json\", get_db_url(), id)"), "{}", firebase);
+    }
+
+    #[test]
+    fn test_register_routes() {
+        let main = "mod db;\n\nuse axum::Router;\n\nfn app() -> Router {\n    Router::new()\n        .route(\"/\", get(root_page))\n        .with_state(app_state)\n}\n";
+        assert_eq!(
+            register_routes(main, "posts").unwrap(),
+            "mod db;\nmod posts;\n\nuse axum::Router;\n\nfn app() -> Router {\n    Router::new()\n        .route(\"/\", get(root_page))\n        .merge(posts::router())\n        .with_state(app_state)\n}\n"
+        );
+        assert!(register_routes("fn main() {}\n", "posts").is_err());
+    }
+
+    #[test]
+    fn test_dioxus_resource_shares_one_model() {
+        let resource = Resource::new("Post", &["title:string".to_string(), "published:bool".to_string()]).unwrap();
+
+        let module = render(&context("postgres", &resource), "generators/dioxus/resource.rs").unwrap();
+        assert!(module.contains("pub struct Post {\n    pub id: i32,\n    pub title: String,\n    pub published: bool,\n}"), "{}", module);
+        assert!(module.contains("#[server(ListPosts, \"/api\")]"));
+        assert!(module.contains("let mut posts = use_resource(list_posts);"));
+        assert!(module.contains("pub fn PostForm(post: ReadOnlySignal<Option<Post>>, on_saved: EventHandler<()>) -> Element"));
+
+        let server = render(&context("postgres", &resource), "generators/dioxus/server.rs").unwrap();
+        assert!(server.contains("use blog::posts::Post;"), "{}", server);
+        assert!(!server.contains("struct Post"));
+        assert!(server.contains("\"UPDATE posts SET title = $1, published = $2 WHERE id = $3\""));
+
+        let mongodb = render(&context("mongodb", &resource), "generators/dioxus/server.rs").unwrap();
+        assert!(mongodb.contains("published: document.get_bool(\"published\").map_err(server_error)?,"), "{}", mongodb);
+        assert!(mongodb.contains("title: document.get_str(\"title\").map_err(server_error)?.to_string(),"));
+        let firebase = render(&context("firebase", &resource), "generators/dioxus/resource.rs").unwrap();
+        assert!(firebase.contains("#[serde(default)]\n    pub id: String,"), "{}", firebase);
+    }
+
+    #[test]
+    fn test_migration_versions_stay_ordered() {
+        let existing = vec!["20260101120000_create_items.up.sql".to_string(), "README.md".to_string()];
+        assert_eq!(next_version(&existing, 20261018093000), 20261018093000);
+        assert_eq!(next_version(&existing, 20260101120000), 20260101120001);
+        assert_eq!(next_version(&[], 20261018093000), 20261018093000);
+
+        let mut context = RenderContext::new("blog");
+        context.insert("db", "mysql").insert(
+            "migration",
+            Value::from_serialize(serde_json::json!({ "name": "add_tags", "label": "add tags" })),
+        );
+        let up = render(&context, "generators/migration.up.sql").unwrap();
+        assert!(up.starts_with("-- add tags (MySQL)."), "{}", up);
+        assert!(up.contains("AUTO_INCREMENT"));
+    }
+
+    #[test]
+    fn test_declare_module() {
+        assert_eq!(
+            declare_module("#![allow(non_snake_case)]\nuse dioxus::prelude::*;\n", "pub mod posts;"),
+            "#![allow(non_snake_case)]\npub mod posts;\nuse dioxus::prelude::*;\n"
+        );
+        assert_eq!(
+            declare_module("mod db;\npub mod posts;\n\nfn main() {}", "mod tags_server;"),
+            "mod db;\npub mod posts;\nmod tags_server;\n\nfn main() {}\n"
+        );
+        assert_eq!(declare_module("fn main() {}\n", "mod posts;"), "mod posts;\nfn main() {}\n");
+    }
+}
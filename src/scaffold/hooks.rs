@@ -0,0 +1,281 @@
+use super::render::RenderContext;
+use super::vcs::git_init;
+use anyhow::{Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// A post-generation step declared by a template with `[[hooks]]`, run in order in the
+/// new project directory.
+///
+/// ```toml
+/// [[hooks]]
+/// copy = { from = ".env.example", to = ".env" }
+///
+/// [[hooks]]
+/// run = "cargo generate-lockfile"
+///
+/// [[hooks]]
+/// builtin = "git-init"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct HookSpec {
+    #[serde(flatten)]
+    pub action: HookAction,
+    /// Report a failure and carry on with the next hook instead of stopping.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// What a hook does. Strings are rendered with the project's variables first.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookAction {
+    /// A shell command (`sh -c`, or `cmd /C` on Windows).
+    Run(String),
+    /// Copy a file inside the project, unless the destination already exists.
+    Copy { from: String, to: String },
+    /// One of the actions Trestle implements itself.
+    Builtin(BuiltinHook),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinHook {
+    /// `git init`, unless the project already is inside a git repository.
+    GitInit,
+}
+
+impl HookSpec {
+    /// The hook as the shell command a user would type instead.
+    pub fn command(&self, context: &RenderContext) -> Result<String> {
+        Ok(match &self.action {
+            HookAction::Run(command) => context.render_str("hook", command)?,
+            HookAction::Copy { from, to } => format!(
+                "cp {} {}",
+                context.render_str("hook", from)?,
+                context.render_str("hook", to)?
+            ),
+            HookAction::Builtin(BuiltinHook::GitInit) => "git init".to_string(),
+        })
+    }
+
+    /// Whether the hook runs a shell command. Templates that do not ship with Trestle only
+    /// get to do that when the user agrees; see [`confirm_hooks`].
+    pub fn runs_shell(&self) -> bool {
+        matches!(self.action, HookAction::Run(_))
+    }
+
+    /// Run the hook in `project_dir`, streaming the output of shell commands.
+    fn run(&self, project_dir: &Path, context: &RenderContext) -> Result<()> {
+        match &self.action {
+            HookAction::Run(command) => {
+                let command = context.render_str("hook", command)?;
+                let status = shell(&command)
+                    .current_dir(project_dir)
+                    .status()
+                    .with_context(|| format!("Failed to start `{}`", command))?;
+                if !status.success() {
+                    anyhow::bail!("`{}` failed ({})", command, status);
+                }
+            }
+            HookAction::Copy { from, to } => {
+                let from = inside_project(project_dir, &context.render_str("hook", from)?)?;
+                let to = inside_project(project_dir, &context.render_str("hook", to)?)?;
+                if to.exists() {
+                    println!("   {} already exists, left as is", to.display());
+                    return Ok(());
+                }
+                fs::copy(&from, &to).with_context(|| {
+                    format!("Failed to copy '{}' to '{}'", from.display(), to.display())
+                })?;
+            }
+            HookAction::Builtin(BuiltinHook::GitInit) => {
+                if !git_init(project_dir)? {
+                    println!("   already in a git repository");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `path` joined to `project_dir`, if it stays inside it. Like file names rendered from a
+/// template (see [`RenderContext::render_path`]), every segment must be a plain name, so a
+/// hook cannot reach `../x` or `/etc/x`, nor follow a symlink out of the project.
+fn inside_project(project_dir: &Path, path: &str) -> Result<PathBuf> {
+    let relative = Path::new(path.trim());
+    let plain = relative.components().all(|c| matches!(c, Component::Normal(_)));
+    let outside = || anyhow::anyhow!("Hook path '{}' is outside the project", path);
+    if relative.as_os_str().is_empty() || !plain {
+        return Err(outside());
+    }
+
+    let target = project_dir.join(relative);
+    let root = project_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve '{}'", project_dir.display()))?;
+    let existing = target.ancestors().find(|p| p.exists()).unwrap_or(project_dir);
+    if !existing.canonicalize()?.starts_with(&root) {
+        return Err(outside());
+    }
+    Ok(target)
+}
+
+/// Run `hooks` in order in `project_dir`.
+///
+/// A failing hook stops the remaining ones (unless it is `optional`); the error lists the
+/// hooks that did not run. For a new project this runs in the staging directory, before
+/// anything is moved into place, so a failure leaves no half-set-up project behind.
+pub fn run_hooks(hooks: &[HookSpec], project_dir: &Path, context: &RenderContext) -> Result<()> {
+    for (i, hook) in hooks.iter().enumerate() {
+        let command = hook.command(context)?;
+        println!("{} Running hook: {}", "→".cyan(), command.bold());
+        let Err(e) = hook.run(project_dir, context) else {
+            continue;
+        };
+        if hook.optional {
+            println!("{} Optional hook failed: {:#}", "!".yellow(), e);
+            continue;
+        }
+
+        let remaining = hooks[i + 1..]
+            .iter()
+            .map(|h| h.command(context))
+            .collect::<Result<Vec<_>>>()?;
+        let mut message = format!("Hook `{}` failed: {:#}", command, e);
+        if !remaining.is_empty() {
+            message.push_str(&format!(
+                "\nThese hooks did not run:\n  {}",
+                remaining.join("\n  ")
+            ));
+        }
+        anyhow::bail!(message);
+    }
+    Ok(())
+}
+
+/// Ask whether the shell commands among `hooks` of template `template_id`, which does not
+/// ship with Trestle, may run. Without a terminal to ask on, they may not.
+pub fn confirm_hooks(hooks: &[HookSpec], template_id: &str, context: &RenderContext) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    println!(
+        "{} Template '{}' is not built in and wants to run these commands:",
+        "?".yellow(),
+        template_id
+    );
+    for hook in hooks.iter().filter(|h| h.runs_shell()) {
+        println!("   {} {}", "→".cyan(), hook.command(context)?);
+    }
+    print!("Run them? [y/N]: ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    fn hooks(toml: &str) -> Vec<HookSpec> {
+        #[derive(Deserialize)]
+        struct Hooks {
+            hooks: Vec<HookSpec>,
+        }
+        toml::from_str::<Hooks>(toml).unwrap().hooks
+    }
+
+    #[test]
+    fn test_hooks_run_in_order_and_stop_on_failure() {
+        let project = assert_fs::TempDir::new().unwrap();
+        project.child(".env.example").write_str("PORT=3000\n").unwrap();
+        let hooks = hooks(
+            r#"
+            [[hooks]]
+            copy = { from = ".env.example", to = ".env" }
+
+            [[hooks]]
+            run = "echo {{ project_snake }} > name.txt"
+
+            [[hooks]]
+            run = "exit 3"
+            optional = true
+
+            [[hooks]]
+            run = "exit 4"
+
+            [[hooks]]
+            builtin = "git-init"
+            "#,
+        );
+        let context = RenderContext::new("hook-app");
+
+        let err = run_hooks(&hooks, project.path(), &context).unwrap_err().to_string();
+        project.child(".env").assert("PORT=3000\n");
+        project.child("name.txt").assert("hook_app\n");
+        assert!(err.contains("Hook `exit 4` failed"), "{}", err);
+        assert!(err.contains("These hooks did not run:\n  git init"), "{}", err);
+        project.child(".git").assert(predicates::path::missing());
+    }
+
+    #[test]
+    fn test_copy_hook_stays_inside_the_project() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let project = temp.child("app");
+        project.child("Cargo.toml").write_str("[package]\n").unwrap();
+        temp.child("secret.txt").write_str("secret\n").unwrap();
+        let context = RenderContext::new("app");
+
+        for (from, to) in [
+            ("Cargo.toml", "../pwned.toml"),
+            ("Cargo.toml", "/tmp/pwned.toml"),
+            ("../secret.txt", "secret.txt"),
+            ("Cargo.toml", "{{ '..' }}/pwned.toml"),
+        ] {
+            let hooks = hooks(&format!("[[hooks]]\ncopy = {{ from = \"{}\", to = \"{}\" }}\n", from, to));
+            let err = run_hooks(&hooks, project.path(), &context).unwrap_err().to_string();
+            assert!(err.contains("is outside the project"), "{} -> {}: {}", from, to, err);
+        }
+        temp.child("pwned.toml").assert(predicates::path::missing());
+        project.child("secret.txt").assert(predicates::path::missing());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(temp.path(), project.path().join("up")).unwrap();
+            let hooks = hooks("[[hooks]]\ncopy = { from = \"Cargo.toml\", to = \"up/pwned.toml\" }\n");
+            assert!(run_hooks(&hooks, project.path(), &context).is_err());
+            temp.child("pwned.toml").assert(predicates::path::missing());
+        }
+    }
+
+    #[test]
+    fn test_copy_hook_keeps_existing_destination() {
+        let project = assert_fs::TempDir::new().unwrap();
+        project.child(".env.example").write_str("PORT=3000\n").unwrap();
+        project.child(".env").write_str("PORT=8080\n").unwrap();
+        let hooks = hooks("[[hooks]]\ncopy = { from = \".env.example\", to = \".env\" }\n");
+
+        run_hooks(&hooks, project.path(), &RenderContext::new("app")).unwrap();
+        project.child(".env").assert("PORT=8080\n");
+    }
+}
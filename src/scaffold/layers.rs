@@ -0,0 +1,313 @@
+use super::render::RenderContext;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Files with this suffix are appended to the file of the same name from earlier layers.
+pub const APPEND_SUFFIX: &str = ".trestle-append";
+/// Files with this suffix remove the file of the same name contributed by earlier layers.
+pub const DELETE_SUFFIX: &str = ".trestle-delete";
+
+/// One layer of a template: a named set of files relative to the project root.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    pub files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+/// A single file of the composed project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedFile {
+    pub contents: Vec<u8>,
+    /// Name of the last layer that wrote or merged into this file.
+    pub layer: String,
+}
+
+/// The in-memory result of rendering and stacking every layer of a template.
+#[derive(Debug, Default, Clone)]
+pub struct RenderedProject {
+    pub files: BTreeMap<PathBuf, RenderedFile>,
+}
+
+impl RenderedProject {
+    /// Render every layer with `context` and stack them in order.
+    ///
+    /// File and directory names are rendered too; see [`RenderContext::render_path`].
+    /// Later layers override earlier files, except for:
+    /// - `Cargo.toml`, whose tables are merged key by key,
+    /// - `.env.example` and `.gitignore`, whose lines are merged by variable/entry,
+    /// - `*.trestle-append` files, appended to the earlier file,
+    /// - `*.trestle-delete` files, which drop the earlier file.
+    pub fn compose(layers: &[Layer], context: &RenderContext) -> Result<Self> {
+        let mut project = Self::default();
+        for layer in layers {
+            for (path, bytes) in &layer.files {
+                // Paths that render to an empty segment are conditionally excluded.
+                let Some(target) = context.render_path(path)? else {
+                    continue;
+                };
+                let rendered = context.render_bytes(&format!("{}/{}", layer.name, path.display()), bytes)?;
+                project.apply(&layer.name, &target, rendered)?;
+            }
+        }
+        Ok(project)
+    }
+
+    fn apply(&mut self, layer: &str, path: &Path, contents: Vec<u8>) -> Result<()> {
+        let path_str = path.to_string_lossy();
+
+        if let Some(target) = path_str.strip_suffix(DELETE_SUFFIX) {
+            self.files.remove(Path::new(target));
+            return Ok(());
+        }
+
+        if let Some(target) = path_str.strip_suffix(APPEND_SUFFIX) {
+            let target = PathBuf::from(target);
+            let entry = self.files.entry(target).or_insert_with(|| RenderedFile {
+                contents: Vec::new(),
+                layer: layer.to_string(),
+            });
+            entry.contents.extend_from_slice(&contents);
+            entry.layer = layer.to_string();
+            return Ok(());
+        }
+
+        let merged = match self.files.get(path) {
+            Some(existing) => merge_file(path, &existing.contents, contents)
+                .with_context(|| format!("Failed to merge '{}' from layer '{}'", path.display(), layer))?,
+            None => contents,
+        };
+        self.files.insert(
+            path.to_path_buf(),
+            RenderedFile {
+                contents: merged,
+                layer: layer.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Names of every crate the root `Cargo.toml` depends on (normal, dev and build).
+    pub fn dependencies(&self) -> Vec<String> {
+        let Some(doc) = self
+            .files
+            .get(Path::new("Cargo.toml"))
+            .and_then(|f| std::str::from_utf8(&f.contents).ok())
+            .and_then(|s| s.parse::<DocumentMut>().ok())
+        else {
+            return Vec::new();
+        };
+
+        ["dependencies", "dev-dependencies", "build-dependencies"]
+            .iter()
+            .filter_map(|table| doc.get(table).and_then(|t| t.as_table_like()))
+            .flat_map(|t| t.iter().map(|(name, _)| name.to_string()))
+            .collect()
+    }
+
+    /// Write every file below `target_dir`, creating parent directories as needed.
+    pub fn write_to(&self, target_dir: &Path) -> Result<()> {
+        for (path, file) in &self.files {
+            let target_path = target_dir.join(path);
+            if let Some(parent) = target_path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            fs::write(&target_path, &file.contents)
+                .with_context(|| format!("Failed to write file: {}", target_path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+fn merge_file(path: &Path, existing: &[u8], incoming: Vec<u8>) -> Result<Vec<u8>> {
+    let (Ok(base), Ok(overlay)) = (std::str::from_utf8(existing), std::str::from_utf8(&incoming)) else {
+        return Ok(incoming);
+    };
+
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some("Cargo.toml") => Ok(merge_toml(base, overlay)?.into_bytes()),
+        Some(".env.example") | Some(".gitignore") => Ok(merge_lines(base, overlay).into_bytes()),
+        _ => Ok(incoming),
+    }
+}
+
+/// Merge `overlay` into `base`, recursing into tables and letting `overlay` win on conflicts.
+pub fn merge_toml(base: &str, overlay: &str) -> Result<String> {
+    let mut base: DocumentMut = base.parse().context("Invalid TOML in earlier layer")?;
+    let overlay: DocumentMut = overlay.parse().context("Invalid TOML in layer")?;
+    let mut next_position = max_position(base.as_table()) + 1;
+    merge_tables(base.as_table_mut(), overlay.as_table(), &mut next_position);
+    Ok(base.to_string())
+}
+
+fn merge_tables(base: &mut Table, overlay: &Table, next_position: &mut isize) {
+    for (key, item) in overlay.iter() {
+        match (base.get_mut(key), item) {
+            (Some(Item::Table(existing)), Item::Table(incoming)) => {
+                merge_tables(existing, incoming, next_position);
+            }
+            (Some(existing), _) => {
+                *existing = item.clone();
+            }
+            (None, Item::Table(incoming)) => {
+                // Place new tables after everything already in the document.
+                let mut table = incoming.clone();
+                reset_positions(&mut table);
+                table.set_position(Some(*next_position));
+                table.decor_mut().set_prefix("\n");
+                *next_position += 1;
+                base.insert(key, Item::Table(table));
+            }
+            (None, _) => {
+                base.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+fn max_position(table: &Table) -> isize {
+    table
+        .iter()
+        .filter_map(|(_, item)| item.as_table())
+        .map(|t| t.position().unwrap_or(0).max(max_position(t)))
+        .max()
+        .unwrap_or(0)
+}
+
+fn reset_positions(table: &mut Table) {
+    for (_, item) in table.iter_mut() {
+        if let Item::Table(t) = item {
+            t.set_position(None);
+            reset_positions(t);
+        }
+    }
+}
+
+/// Merge line-oriented `KEY=value` files: a variable from `overlay` replaces the
+/// same variable in `base`, other lines are appended unless already present.
+pub fn merge_lines(base: &str, overlay: &str) -> String {
+    let mut lines: Vec<String> = base.lines().map(String::from).collect();
+    for line in overlay.lines() {
+        if let Some(key) = env_key(line) {
+            if let Some(existing) = lines.iter_mut().find(|l| env_key(l) == Some(key)) {
+                *existing = line.to_string();
+                continue;
+            }
+        } else if line.trim().is_empty() {
+            if lines.last().is_some_and(|l| l.trim().is_empty()) {
+                continue;
+            }
+        } else if lines.iter().any(|l| l == line) {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+
+    let mut merged = lines.join("\n");
+    if base.ends_with('\n') || overlay.ends_with('\n') {
+        merged.push('\n');
+    }
+    merged
+}
+
+/// Merge generated lines into a file the user already has: like [`merge_lines`], except
+/// that the user's value of a variable wins over the generated one.
+pub fn merge_lines_into_existing(existing: &str, generated: &str) -> String {
+    let missing: Vec<&str> = generated
+        .lines()
+        .filter(|line| env_key(line).is_none_or(|key| !existing.lines().any(|l| env_key(l) == Some(key))))
+        .collect();
+    let mut overlay = missing.join("\n");
+    if !missing.is_empty() && generated.ends_with('\n') {
+        overlay.push('\n');
+    }
+    merge_lines(existing, &overlay)
+}
+
+fn env_key(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if line.starts_with('#') {
+        return None;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    line.split_once('=').map(|(key, _)| key.trim())
+}
+
+#[cfg(test)]
+impl RenderedProject {
+    /// A project of `files`, all from the `base` layer.
+    pub fn from_files<C: AsRef<[u8]>>(files: &[(&str, C)]) -> Self {
+        let mut project = Self::default();
+        for (path, contents) in files {
+            project.files.insert(
+                PathBuf::from(path),
+                RenderedFile {
+                    contents: contents.as_ref().to_vec(),
+                    layer: "base".to_string(),
+                },
+            );
+        }
+        project
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(name: &str, files: &[(&str, &str)]) -> Layer {
+        Layer {
+            name: name.to_string(),
+            files: files
+                .iter()
+                .map(|(p, c)| (PathBuf::from(p), c.as_bytes().to_vec()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_toml_combines_dependency_tables() {
+        let base = "[package]\nname = \"app\"\n\n[dependencies]\naxum = \"0.7.5\"\ntokio = { version = \"1\", features = [\"full\"] }\n";
+        let overlay = "[lib]\ncrate-type = [\"rlib\"]\n\n[dependencies]\naxum = { version = \"0.7.5\", features = [\"json\"] }\nmaud = \"0.26.0\"\n";
+
+        let merged = merge_toml(base, overlay).unwrap();
+        assert_eq!(
+            merged,
+            "[package]\nname = \"app\"\n\n[dependencies]\naxum = { version = \"0.7.5\", features = [\"json\"] }\ntokio = { version = \"1\", features = [\"full\"] }\nmaud = \"0.26.0\"\n\n[lib]\ncrate-type = [\"rlib\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_lines_replaces_variables_and_skips_duplicates() {
+        let merged = merge_lines(
+            "# App\nRUST_LOG=info\nPORT=3000\n",
+            "# App\nPORT=8080\nDATABASE_URL=\"postgres://localhost\"\n",
+        );
+        assert_eq!(merged, "# App\nRUST_LOG=info\nPORT=8080\nDATABASE_URL=\"postgres://localhost\"\n");
+    }
+
+    #[test]
+    fn test_compose_overrides_appends_and_deletes() {
+        let layers = [
+            layer("base", &[("README.md", "base\n"), ("src/main.rs", "fn main() {}\n"), ("notes.txt", "x")]),
+            layer(
+                "overlay",
+                &[
+                    ("src/main.rs", "fn main() { {{ project_name }}(); }\n"),
+                    ("README.md.trestle-append", "more\n"),
+                    ("notes.txt.trestle-delete", ""),
+                ],
+            ),
+        ];
+
+        let project = RenderedProject::compose(&layers, &RenderContext::new("demo")).unwrap();
+        let files: Vec<_> = project.files.keys().map(|p| p.to_str().unwrap()).collect();
+        assert_eq!(files, vec!["README.md", "src/main.rs"]);
+        assert_eq!(project.files[Path::new("README.md")].contents, b"base\nmore\n");
+        assert_eq!(project.files[Path::new("src/main.rs")].contents, b"fn main() { demo(); }\n");
+        assert_eq!(project.files[Path::new("src/main.rs")].layer, "overlay");
+    }
+}
@@ -0,0 +1,329 @@
+use super::layers::{Layer, RenderedFile, RenderedProject};
+use super::pack::sha256_hex;
+use super::render::RenderContext;
+use super::source::{self, ArchiveSource, EmbeddedSource, GitSource, TemplateSource};
+use super::{TemplateLocation, TEMPLATES};
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use minijinja::Value;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Where the lockfile lives inside a generated project.
+pub const LOCK_FILE: &str = ".trestle/project.toml";
+
+/// The generated files as the template rendered them, kept as the merge base for
+/// `trestle update` and the reference for `trestle diff`.
+pub const BASE_FILE: &str = ".trestle/base.tar.gz";
+
+/// How a project was generated, written to `.trestle/project.toml`.
+///
+/// It records everything needed to render the same template again (to detect drift
+/// or to upgrade the project) and the hash of every file as it was generated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectLock {
+    /// Version of Trestle that generated the project.
+    pub trestle_version: String,
+    pub template: TemplateRecord,
+    /// The render context, minus the name variants derived from `project_name`.
+    pub variables: BTreeMap<String, toml::Value>,
+    /// SHA-256 of every generated file, by path relative to the project root.
+    pub files: BTreeMap<String, String>,
+}
+
+/// Identity of the template a project was generated from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateRecord {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// `embedded`, `directory`, `archive` or `git`.
+    pub kind: String,
+    /// `embedded`, an absolute path, or a git URL.
+    pub source: String,
+    /// The template directory inside the source, when not at its root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Resolved commit of a git template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// SHA-256 over every file of the template and its layers.
+    pub checksum: String,
+}
+
+impl ProjectLock {
+    pub fn new(template: TemplateRecord, context: &RenderContext, project: &RenderedProject) -> Result<Self> {
+        let mut variables = BTreeMap::new();
+        for (key, value) in context.variables() {
+            // TOML has no null; unset variables (e.g. `db` for static sites) are left out.
+            if value.is_none() || value.is_undefined() {
+                continue;
+            }
+            let value = toml::Value::try_from(&value)
+                .with_context(|| format!("Variable '{}' cannot be recorded", key))?;
+            variables.insert(key, value);
+        }
+
+        Ok(Self {
+            trestle_version: env!("CARGO_PKG_VERSION").to_string(),
+            template,
+            variables,
+            files: file_hashes(project),
+        })
+    }
+
+    /// Whether `project` holds exactly the files recorded in the lockfile, e.g. to tell a
+    /// stored render from a stale one left by an earlier generation.
+    pub fn describes(&self, project: &RenderedProject) -> bool {
+        file_hashes(project) == self.files
+    }
+
+    /// Read the lockfile of the project in `project_dir`.
+    pub fn read(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(LOCK_FILE);
+        let content = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "No {} in '{}'; was the project generated by Trestle?",
+                LOCK_FILE,
+                project_dir.display()
+            )
+        })?;
+        let lock: Self =
+            toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+        // The lockfile is committed with the project, so whoever can push to the repository
+        // controls it; `trestle update` must not pass arbitrary values on to git.
+        lock.template
+            .validate()
+            .with_context(|| format!("Invalid template record in {}", path.display()))?;
+        Ok(lock)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(format!(
+            "# Written by Trestle. Records how this project was generated; do not edit by hand.\n{}",
+            toml::to_string(self)?
+        ))
+    }
+
+    /// The render context the project was generated with.
+    pub fn context(&self) -> Result<RenderContext> {
+        let vars: BTreeMap<String, Value> = self
+            .variables
+            .iter()
+            .map(|(key, value)| (key.clone(), Value::from_serialize(value)))
+            .collect();
+        RenderContext::from_variables(&vars)
+    }
+}
+
+impl TemplateRecord {
+    /// Open the latest version of the recorded template; a git template at the head of its
+    /// default branch.
+    pub fn locate(&self) -> Result<TemplateLocation> {
+        let source: Arc<dyn TemplateSource> = match self.kind.as_str() {
+            "embedded" => Arc::new(EmbeddedSource::new(&TEMPLATES)),
+            "git" => Arc::new(GitSource::clone(&self.source, None)?),
+            _ => source::open(Path::new(&self.source))?,
+        };
+        Ok(TemplateLocation {
+            source,
+            path: PathBuf::from(self.path.clone().unwrap_or_default()),
+        })
+    }
+
+    /// Check a record read from disk: a known kind, a git URL or absolute path as the
+    /// source, a hex commit and a relative template path.
+    fn validate(&self) -> Result<()> {
+        let source_ok = match self.kind.as_str() {
+            "embedded" => self.source == "embedded",
+            "directory" | "archive" => Path::new(&self.source).is_absolute(),
+            "git" => is_git_url(&self.source),
+            other => anyhow::bail!("unknown template kind '{}'", other),
+        };
+        if !source_ok {
+            anyhow::bail!("'{}' is not a valid source for a {} template", self.source, self.kind);
+        }
+        if let Some(commit) = &self.commit {
+            if commit.is_empty() || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+                anyhow::bail!("'{}' is not a commit hash", commit);
+            }
+        }
+        if let Some(path) = &self.path {
+            if !Path::new(path).components().all(|c| matches!(c, Component::Normal(_))) {
+                anyhow::bail!("'{}' is not a relative template path", path);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `scheme://...` or scp-like `user@host:path`, and never something git reads as an option.
+fn is_git_url(url: &str) -> bool {
+    if url.starts_with('-') || url.chars().any(char::is_whitespace) {
+        return false;
+    }
+    match url.split_once("://") {
+        Some((scheme, rest)) => {
+            !scheme.is_empty()
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+                && !rest.is_empty()
+        }
+        None => url
+            .split_once(':')
+            .is_some_and(|(host, path)| host.contains('@') && !host.contains('/') && !path.is_empty()),
+    }
+}
+
+/// SHA-256 of every file of `project` but the lockfile, by `/`-separated path.
+fn file_hashes(project: &RenderedProject) -> BTreeMap<String, String> {
+    project
+        .files
+        .iter()
+        .filter(|(path, _)| *path != Path::new(LOCK_FILE) && *path != Path::new(BASE_FILE))
+        .map(|(path, file)| (path.to_string_lossy().replace('\\', "/"), sha256_hex(&file.contents)))
+        .collect()
+}
+
+/// Pack the files of `project` into the `.tar.gz` stored at [`BASE_FILE`]. Entries carry
+/// no timestamps, so the same render always gives the same archive.
+pub fn pack_base(project: &RenderedProject) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (path, file) in &project.files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(file.contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, file.contents.as_slice())
+            .with_context(|| format!("Failed to record '{}'", path.display()))?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// The render stored at [`BASE_FILE`] in `project_dir`, or `None` for projects generated
+/// before Trestle kept one.
+pub fn read_base(project_dir: &Path) -> Result<Option<RenderedProject>> {
+    let path = project_dir.join(BASE_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let archive = ArchiveSource::open(&path)?;
+    let mut project = RenderedProject::default();
+    for path in archive.files()? {
+        let contents = archive.read(&path)?;
+        project.files.insert(
+            path,
+            RenderedFile {
+                contents,
+                layer: "trestle".to_string(),
+            },
+        );
+    }
+    Ok(Some(project))
+}
+
+/// SHA-256 over the names, paths and contents of every layer, in order.
+pub fn layers_checksum(layers: &[Layer]) -> String {
+    let mut hasher = Sha256::new();
+    for layer in layers {
+        hasher.update(layer.name.as_bytes());
+        hasher.update([0]);
+        for (path, contents) in &layer.files {
+            hasher.update(path.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update((contents.len() as u64).to_le_bytes());
+            hasher.update(contents);
+        }
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockfile_round_trips_variables_and_hashes() {
+        let mut context = RenderContext::new("my-app");
+        context
+            .insert("frontend", "htmx")
+            .insert("addons", Value::from_serialize(BTreeMap::from([("tailwind", true)])));
+        let project = RenderedProject::from_files(&[("src/main.rs", "fn main() {}\n")]);
+        let record = TemplateRecord {
+            id: "static-htmx".to_string(),
+            version: None,
+            kind: "embedded".to_string(),
+            source: "embedded".to_string(),
+            path: Some("static-htmx".to_string()),
+            commit: None,
+            checksum: "abc".to_string(),
+        };
+
+        let lock = ProjectLock::new(record, &context, &project).unwrap();
+        assert_eq!(lock.files["src/main.rs"], sha256_hex(b"fn main() {}\n"));
+        assert!(!lock.variables.contains_key("db"));
+        assert!(!lock.variables.contains_key("project_snake"));
+
+        let toml = lock.to_toml().unwrap();
+        assert!(toml.contains("[template]\nid = \"static-htmx\""), "{}", toml);
+        let parsed: ProjectLock = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed, lock);
+
+        let restored = parsed.context().unwrap();
+        assert_eq!(
+            restored.render_str("t", "{{ project_snake }} {{ frontend }}{% if addons.tailwind %} tailwind{% endif %}{% if db is none %} static{% endif %}").unwrap(),
+            "my_app htmx tailwind static"
+        );
+    }
+
+    #[test]
+    fn test_read_rejects_records_that_would_reach_git_as_options() {
+        let project = assert_fs::TempDir::new().unwrap();
+        fs::create_dir(project.path().join(".trestle")).unwrap();
+        let read = |template: &str| {
+            let lock = format!(
+                "trestle_version = \"0.1.1\"\nvariables = {{}}\nfiles = {{}}\n\n[template]\nid = \"house\"\nchecksum = \"abc\"\n{}",
+                template
+            );
+            fs::write(project.path().join(LOCK_FILE), lock).unwrap();
+            ProjectLock::read(project.path()).map_err(|e| format!("{:#}", e))
+        };
+
+        read("kind = \"git\"\nsource = \"file:///srv/house.git\"\ncommit = \"0a1b2c\"\n").unwrap();
+        read("kind = \"git\"\nsource = \"git@example.com:house.git\"\n").unwrap();
+        read("kind = \"directory\"\nsource = \"/srv/house\"\npath = \"templates/house\"\n").unwrap();
+        for (template, error) in [
+            ("kind = \"git\"\nsource = \"--upload-pack=touch x\"\n", "not a valid source"),
+            ("kind = \"git\"\nsource = \"file:///srv/house.git\"\ncommit = \"--output=x\"\n", "not a commit hash"),
+            ("kind = \"directory\"\nsource = \"house\"\n", "not a valid source"),
+            ("kind = \"embedded\"\nsource = \"embedded\"\npath = \"../house\"\n", "not a relative template path"),
+            ("kind = \"ftp\"\nsource = \"embedded\"\n", "unknown template kind"),
+        ] {
+            let err = read(template).unwrap_err();
+            assert!(err.contains(error), "{}: {}", template, err);
+        }
+    }
+
+    #[test]
+    fn test_stored_render_round_trips() {
+        let project = assert_fs::TempDir::new().unwrap();
+        assert!(read_base(project.path()).unwrap().is_none());
+
+        let rendered =
+            RenderedProject::from_files(&[("Cargo.toml", &b"[package]\n"[..]), ("static/logo.png", &[0, 159, 146][..])]);
+        let archive = pack_base(&rendered).unwrap();
+        assert_eq!(archive, pack_base(&rendered).unwrap());
+        fs::create_dir(project.path().join(".trestle")).unwrap();
+        fs::write(project.path().join(BASE_FILE), archive).unwrap();
+
+        let stored = read_base(project.path()).unwrap().unwrap();
+        assert_eq!(stored.files.keys().collect::<Vec<_>>(), rendered.files.keys().collect::<Vec<_>>());
+        assert_eq!(stored.files[Path::new("static/logo.png")].contents, vec![0, 159, 146]);
+    }
+}
@@ -0,0 +1,218 @@
+use super::hooks::HookSpec;
+use super::source::TemplateSource;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the manifest file every template directory must contain.
+pub const MANIFEST_FILE: &str = "trestle-template.toml";
+
+/// Metadata describing a single template, read from its `trestle-template.toml`.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TemplateManifest {
+    /// Unique identifier, usually the template directory name (e.g. `postgres-htmx`).
+    pub id: String,
+    /// One-line, human readable summary of the stack.
+    pub description: String,
+    /// Version of the template, used when packing it (e.g. `1.2`).
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Frontend this template targets (e.g. `dioxus`, `htmx`).
+    pub frontend: String,
+    /// Database this template targets. Omitted for static sites.
+    #[serde(default)]
+    pub db: Option<String>,
+    /// Environment variables the generated project expects to be set.
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// Steps printed after generation, following the implicit `cd <project>`.
+    #[serde(default)]
+    pub next_steps: Vec<String>,
+    /// Post-generation hooks, run in order in the new project directory.
+    #[serde(default)]
+    pub hooks: Vec<HookSpec>,
+    /// Layers stacked underneath this template's own files, in order. Paths are
+    /// relative to the templates root (e.g. `layers/base`).
+    #[serde(default)]
+    pub layers: Vec<String>,
+    /// Optional add-ons, exposed to templates as `addons.<name>` booleans.
+    #[serde(default)]
+    pub addons: BTreeMap<String, AddonSpec>,
+}
+
+/// An optional feature a template can toggle on or off.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AddonSpec {
+    pub description: String,
+    /// Whether the add-on is enabled when the user does not choose.
+    #[serde(default)]
+    pub default: bool,
+}
+
+impl TemplateManifest {
+    pub fn parse(content: &str) -> Result<Self> {
+        let manifest: Self = toml::from_str(content)?;
+        validate_id(&manifest.id)?;
+        Ok(manifest)
+    }
+
+    /// Whether this template serves the requested frontend/database pairing.
+    pub fn matches(&self, frontend: &str, db: Option<&str>) -> bool {
+        self.frontend == frontend && self.db.as_deref() == db
+    }
+
+    /// Resolve the final add-on selection from the manifest defaults and the
+    /// names the user explicitly enabled or disabled.
+    pub fn resolve_addons(
+        &self,
+        enabled: &[String],
+        disabled: &[String],
+    ) -> Result<BTreeMap<String, bool>> {
+        for name in enabled.iter().chain(disabled) {
+            if !self.addons.contains_key(name) {
+                let known: Vec<_> = self.addons.keys().map(String::as_str).collect();
+                anyhow::bail!(
+                    "Template '{}' has no add-on named '{}' (available: {})",
+                    self.id,
+                    name,
+                    if known.is_empty() { "none".to_string() } else { known.join(", ") }
+                );
+            }
+        }
+
+        Ok(self
+            .addons
+            .iter()
+            .map(|(name, spec)| {
+                let on = if disabled.contains(name) {
+                    false
+                } else {
+                    enabled.contains(name) || spec.default
+                };
+                (name.clone(), on)
+            })
+            .collect())
+    }
+}
+
+/// The id names the template's directory when it is installed and its archive when it is
+/// packed, so it must be a single plain path segment.
+fn validate_id(id: &str) -> Result<()> {
+    let mut components = Path::new(id).components();
+    let single = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(name)), None) if name == id
+    );
+    if !single || id.starts_with('.') || id.contains('\\') {
+        anyhow::bail!(
+            "Invalid template id '{}': it must be a single directory name, without '/', '..' or a leading '.'",
+            id
+        );
+    }
+    Ok(())
+}
+
+/// Read the manifest of every template directory directly below the root of `source`.
+pub fn manifests(source: &dyn TemplateSource) -> Result<Vec<(TemplateManifest, PathBuf)>> {
+    let mut manifests = Vec::new();
+    for path in source.files()? {
+        let Some(dir) = path.parent() else {
+            continue;
+        };
+        if path.file_name() != Some(MANIFEST_FILE.as_ref()) || dir.components().count() != 1 {
+            continue;
+        }
+        let manifest = read(source, dir)?;
+        manifests.push((manifest, dir.to_path_buf()));
+    }
+    Ok(manifests)
+}
+
+/// Read the manifest of the template directory `dir` inside `source`.
+pub fn read(source: &dyn TemplateSource, dir: &Path) -> Result<TemplateManifest> {
+    let path = dir.join(MANIFEST_FILE);
+    let bytes = source.read(&path)?;
+    let content = String::from_utf8(bytes)
+        .map_err(|_| anyhow::anyhow!("Manifest '{}' is not valid UTF-8", path.display()))?;
+    TemplateManifest::parse(&content).with_context(|| {
+        format!("Failed to parse manifest: {} ({})", path.display(), source.describe())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest_and_match() {
+        let manifest = TemplateManifest::parse(
+            r#"
+            id = "postgres-htmx"
+            description = "Axum + HTMX with PostgreSQL"
+            frontend = "htmx"
+            db = "postgres"
+            env = ["DATABASE_URL"]
+            next_steps = ["cargo run"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.id, "postgres-htmx");
+        assert_eq!(manifest.env, vec!["DATABASE_URL".to_string()]);
+        assert!(manifest.matches("htmx", Some("postgres")));
+        assert!(!manifest.matches("htmx", None));
+        assert!(!manifest.matches("dioxus", Some("postgres")));
+    }
+
+    #[test]
+    fn test_parse_rejects_ids_that_are_not_a_single_directory_name() {
+        for id in ["../escaped", "a/b", "/abs", "..", ".hidden", "", "a\\b", "house/"] {
+            let manifest = format!("id = '{}'\ndescription = \"\"\nfrontend = \"htmx\"\n", id);
+            let err = TemplateManifest::parse(&manifest).unwrap_err().to_string();
+            assert!(err.contains("Invalid template id"), "{}: {}", id, err);
+        }
+    }
+
+    #[test]
+    fn test_resolve_addons() {
+        let manifest = TemplateManifest::parse(
+            r#"
+            id = "static-htmx"
+            description = "Static"
+            frontend = "htmx"
+
+            [addons.tailwind]
+            description = "Tailwind CSS"
+            default = true
+
+            [addons.analytics]
+            description = "Analytics snippet"
+            "#,
+        )
+        .unwrap();
+
+        let addons = manifest.resolve_addons(&[], &[]).unwrap();
+        assert!(addons["tailwind"]);
+        assert!(!addons["analytics"]);
+
+        let addons = manifest
+            .resolve_addons(&["analytics".to_string()], &["tailwind".to_string()])
+            .unwrap();
+        assert!(!addons["tailwind"]);
+        assert!(addons["analytics"]);
+
+        assert!(manifest.resolve_addons(&["nope".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_every_embedded_template_has_a_manifest() {
+        let source = crate::scaffold::source::EmbeddedSource::new(&crate::scaffold::TEMPLATES);
+        let manifests = manifests(&source).unwrap();
+        let ids: Vec<_> = manifests.iter().map(|(m, _)| m.id.as_str()).collect();
+        assert_eq!(ids.len(), 10, "expected ten built-in stacks, got {:?}", ids);
+        for (manifest, path) in &manifests {
+            assert_eq!(path.to_str(), Some(manifest.id.as_str()));
+        }
+    }
+}
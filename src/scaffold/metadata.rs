@@ -0,0 +1,195 @@
+use super::layers::{RenderedFile, RenderedProject};
+use super::render::RenderContext;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Array, DocumentMut};
+
+/// License texts shipped with Trestle, by SPDX id. `[year]` and `[fullname]` are filled in
+/// with the project's copyright line.
+pub const LICENSE_TEXTS: &[(&str, &str)] = &[
+    ("MIT", include_str!("licenses/MIT.txt")),
+    ("Apache-2.0", include_str!("licenses/Apache-2.0.txt")),
+    ("BSD-3-Clause", include_str!("licenses/BSD-3-Clause.txt")),
+    ("MPL-2.0", include_str!("licenses/MPL-2.0.txt")),
+    ("GPL-3.0-only", include_str!("licenses/GPL-3.0.txt")),
+    ("GPL-3.0-or-later", include_str!("licenses/GPL-3.0.txt")),
+];
+
+/// Fill the `[package]` table of the rendered `Cargo.toml` from the `author`, `license`,
+/// `description` and `repository` variables, and add a LICENSE file for every license of
+/// the `license` expression. Unset variables leave the template's values alone, and files
+/// the template ships itself are never replaced.
+pub fn apply(project: &mut RenderedProject, context: &RenderContext) -> Result<()> {
+    let author = string(context, "author");
+    let license = string(context, "license");
+    let description = string(context, "description");
+    let repository = string(context, "repository");
+
+    if let Some(manifest) = project.files.get_mut(Path::new("Cargo.toml")) {
+        let content = String::from_utf8_lossy(&manifest.contents);
+        let mut doc: DocumentMut = content.parse().context("Failed to parse the generated Cargo.toml")?;
+        if let Some(package) = doc.get_mut("package").and_then(|p| p.as_table_like_mut()) {
+            if let Some(author) = &author {
+                package.insert("authors", value(Array::from_iter([author.as_str()])));
+            }
+            for (key, field) in [("license", &license), ("description", &description), ("repository", &repository)] {
+                if let Some(field) = field {
+                    package.insert(key, value(field.as_str()));
+                }
+            }
+            manifest.contents = doc.to_string().into_bytes();
+        }
+    }
+
+    let Some(license) = license else {
+        return Ok(());
+    };
+    let holder = author
+        .as_deref()
+        .map(|a| a.split('<').next().unwrap_or(a).trim().to_string())
+        .unwrap_or_else(|| format!("The {} Authors", context.names().raw));
+    let year = context
+        .get("timestamp")
+        .and_then(|t| t.as_str().and_then(|t| t.get(..4)).map(str::to_string))
+        .unwrap_or_else(|| chrono::Utc::now().format("%Y").to_string());
+    for (path, text) in license_files(&license) {
+        if project.files.contains_key(&path) {
+            continue;
+        }
+        let text = text.replace("[year]", &year).replace("[fullname]", &holder);
+        project.files.insert(
+            path,
+            RenderedFile {
+                contents: text.into_bytes(),
+                layer: "trestle".to_string(),
+            },
+        );
+    }
+    Ok(())
+}
+
+/// The license ids of an SPDX expression such as `MIT OR Apache-2.0`, without the
+/// exceptions named after `WITH`.
+pub fn license_ids(expression: &str) -> Vec<&str> {
+    let mut ids = Vec::new();
+    let mut exception = false;
+    for token in expression.split_whitespace() {
+        let token = token.trim_matches(|c| c == '(' || c == ')');
+        match token {
+            "" | "OR" | "AND" => {}
+            "WITH" => exception = true,
+            _ if exception => exception = false,
+            _ if !ids.contains(&token) => ids.push(token),
+            _ => {}
+        }
+    }
+    ids
+}
+
+/// Ids of `expression` Trestle has no license text for.
+pub fn unknown_licenses(expression: &str) -> Vec<&str> {
+    license_ids(expression)
+        .into_iter()
+        .filter(|id| !LICENSE_TEXTS.iter().any(|(known, _)| known == id))
+        .collect()
+}
+
+/// The files for `expression`: `LICENSE` for a single license, `LICENSE-MIT`,
+/// `LICENSE-Apache-2.0`, ... when it names several, even if only some have a known text,
+/// so a lone `LICENSE` never suggests the project has only that license.
+fn license_files(expression: &str) -> Vec<(PathBuf, &'static str)> {
+    let ids = license_ids(expression);
+    let several = ids.len() > 1;
+    ids.into_iter()
+        .filter_map(|id| LICENSE_TEXTS.iter().find(|(known, _)| *known == id))
+        .map(|(id, text)| {
+            let name = if several { format!("LICENSE-{}", id) } else { "LICENSE".to_string() };
+            (PathBuf::from(name), *text)
+        })
+        .collect()
+}
+
+/// A variable as a non-empty string.
+fn string(context: &RenderContext, key: &str) -> Option<String> {
+    let value = context.get(key)?.as_str()?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::Value;
+
+    fn project() -> RenderedProject {
+        RenderedProject::from_files(&[(
+            "Cargo.toml",
+            "[package]\nname = \"crate-app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\naxum = \"0.7.5\"\n",
+        )])
+    }
+
+    #[test]
+    fn test_apply_fills_package_and_writes_dual_licenses() {
+        let mut context = RenderContext::new("crate-app");
+        context
+            .insert("timestamp", "20260101120000")
+            .insert("author", "Ada Lovelace <ada@example.com>")
+            .insert("license", "MIT OR Apache-2.0")
+            .insert("description", "An internal crate")
+            .insert("repository", Value::from(()));
+        let mut project = project();
+        apply(&mut project, &context).unwrap();
+
+        let manifest = String::from_utf8(project.files[Path::new("Cargo.toml")].contents.clone()).unwrap();
+        assert_eq!(
+            manifest,
+            "[package]\nname = \"crate-app\"\nversion = \"0.1.0\"\nedition = \"2021\"\nauthors = [\"Ada Lovelace <ada@example.com>\"]\nlicense = \"MIT OR Apache-2.0\"\ndescription = \"An internal crate\"\n\n[dependencies]\naxum = \"0.7.5\"\n"
+        );
+        let mit = String::from_utf8(project.files[Path::new("LICENSE-MIT")].contents.clone()).unwrap();
+        assert!(mit.contains("Copyright (c) 2026 Ada Lovelace\n"), "{}", mit);
+        assert!(project.files.contains_key(Path::new("LICENSE-Apache-2.0")));
+        assert!(!project.files.contains_key(Path::new("LICENSE")));
+    }
+
+    #[test]
+    fn test_single_and_unknown_licenses() {
+        let mut context = RenderContext::new("crate-app");
+        context.insert("license", "GPL-3.0-only");
+        let mut project = project();
+        apply(&mut project, &context).unwrap();
+        assert!(project.files.contains_key(Path::new("LICENSE")));
+
+        assert_eq!(license_ids("(MIT OR Apache-2.0) AND BSD-3-Clause"), vec!["MIT", "Apache-2.0", "BSD-3-Clause"]);
+        assert_eq!(unknown_licenses("MIT OR Unlicense"), vec!["Unlicense"]);
+        assert!(license_files("Unlicense").is_empty());
+    }
+
+    #[test]
+    fn test_mixed_expression_writes_suffixed_file_for_the_known_license() {
+        let files: Vec<_> = license_files("MIT OR LicenseRef-Foo").into_iter().map(|(path, _)| path).collect();
+        assert_eq!(files, vec![PathBuf::from("LICENSE-MIT")]);
+    }
+
+    #[test]
+    fn test_with_exception_is_not_a_license() {
+        assert_eq!(license_ids("GPL-3.0-or-later WITH Classpath-exception-2.0"), vec!["GPL-3.0-or-later"]);
+        assert_eq!(license_ids("(Apache-2.0 WITH LLVM-exception) OR MIT"), vec!["Apache-2.0", "MIT"]);
+        assert!(unknown_licenses("GPL-3.0-only WITH Classpath-exception-2.0").is_empty());
+        let files: Vec<_> = license_files("GPL-3.0-only WITH Classpath-exception-2.0")
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(files, vec![PathBuf::from("LICENSE")]);
+    }
+
+    #[test]
+    fn test_license_variants_get_their_own_files() {
+        let files: Vec<_> = license_files("GPL-3.0-only OR GPL-3.0-or-later")
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("LICENSE-GPL-3.0-only"), PathBuf::from("LICENSE-GPL-3.0-or-later")]
+        );
+    }
+}
@@ -1,48 +1,280 @@
-use anyhow::{Context, Result};
+pub mod catalog;
+pub mod config;
+pub mod conflicts;
+pub mod drift;
+pub mod generate;
+pub mod hooks;
+pub mod layers;
+pub mod lockfile;
+pub mod manifest;
+pub mod metadata;
+pub mod naming;
+pub mod pack;
+pub mod preview;
+pub mod render;
+pub mod source;
+pub mod staging;
+pub mod update;
+pub mod validate;
+pub mod vcs;
+
+use anyhow::Result;
 use colored::*;
-use std::fs;
-use std::path::Path;
-use walkdir::WalkDir;
+use conflicts::{write_into_existing, ConflictStrategy, WriteSummary};
+use drift::Drift;
+use include_dir::{include_dir, Dir};
+use layers::{Layer, RenderedFile, RenderedProject};
+use lockfile::{ProjectLock, TemplateRecord, BASE_FILE, LOCK_FILE};
+use manifest::{TemplateManifest, MANIFEST_FILE};
+use render::RenderContext;
+use source::{EmbeddedSource, GitSource, TemplateSource};
+use staging::Staging;
+use update::{Base, Update, UpdateSummary};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use validate::validate_package_name;
+
+/// Templates bundled into the binary (works in crates.io installs).
+pub static TEMPLATES: Dir = include_dir!("templates");
+
+/// Where a resolved template lives: a directory inside a template source.
+#[derive(Debug, Clone)]
+pub struct TemplateLocation {
+    pub source: Arc<dyn TemplateSource>,
+    /// The template directory, relative to the root of `source`.
+    pub path: PathBuf,
+}
+
+impl TemplateLocation {
+    /// Open a single template given by path: a directory or a `.tar.gz` archive.
+    /// See [`TemplateLocation::from_source`] for how the template is found inside it.
+    pub fn from_path(path: &Path, subdir: Option<&Path>) -> Result<(TemplateManifest, Self)> {
+        if !path.exists() {
+            anyhow::bail!("Template '{}' does not exist", path.display());
+        }
+        Self::from_source(source::open(path)?, subdir)
+    }
+
+    /// The template inside `source`: the directory `subdir` if given, otherwise the root
+    /// when it holds a `trestle-template.toml`, otherwise the only top-level directory
+    /// that does.
+    pub fn from_source(
+        source: Arc<dyn TemplateSource>,
+        subdir: Option<&Path>,
+    ) -> Result<(TemplateManifest, Self)> {
+        let files = source.files()?;
+        let template_dir = match subdir {
+            Some(dir) => {
+                if !files.contains(&dir.join(MANIFEST_FILE)) {
+                    anyhow::bail!(
+                        "No {} found in '{}' of template '{}'",
+                        MANIFEST_FILE,
+                        dir.display(),
+                        source.describe()
+                    );
+                }
+                dir.to_path_buf()
+            }
+            None if files.iter().any(|p| p == Path::new(MANIFEST_FILE)) => PathBuf::new(),
+            None => match manifest::manifests(source.as_ref())?.as_slice() {
+                [(_, dir)] => dir.clone(),
+                _ => anyhow::bail!(
+                    "No {} found in template '{}'",
+                    MANIFEST_FILE,
+                    source.describe()
+                ),
+            },
+        };
+        let manifest = manifest::read(source.as_ref(), &template_dir)?;
+        Ok((
+            manifest,
+            Self {
+                source,
+                path: template_dir,
+            },
+        ))
+    }
+}
+
+/// The per-user template directory: `$XDG_CONFIG_HOME/trestle/templates`, falling back
+/// to `~/.config/trestle/templates`.
+pub fn user_templates_dir() -> Option<PathBuf> {
+    Some(config::user_config_dir()?.join("templates"))
+}
 
-const PLACEHOLDER: &str = "__PROJECT_NAME__";
+/// List every available template. User templates come first, so one with the same id
+/// as a built-in template replaces it; then those in the `template_paths` of the user
+/// config, then the embedded templates, then those in the `template_paths` of the repo
+/// config, then those found under `templates/` in the current directory (for local
+/// development).
+///
+/// A repository can add templates but not replace the built-in ones or the user's:
+/// a repo-config template whose id is already taken is skipped with a warning.
+///
+/// The user template directory may hold template directories as well as `.tar.gz`
+/// archives, which are read in place.
+pub fn available_templates() -> Result<Vec<(TemplateManifest, TemplateLocation)>> {
+    let settings = config::Settings::load_current()?;
+    let mut found = Vec::new();
+    if let Some(dir) = user_templates_dir() {
+        found.extend(templates_in(source::open(&dir)?)?);
+        for archive in user_archives(&dir)? {
+            found.push(TemplateLocation::from_path(&archive, None)?);
+        }
+    }
+    for dir in settings.user_template_paths() {
+        found.extend(templates_in(source::open(&dir)?)?);
+    }
+    found.extend(templates_in(Arc::new(EmbeddedSource::new(&TEMPLATES)))?);
+    let trusted = found.len();
+    for dir in settings.repo_template_paths() {
+        found.extend(templates_in(source::open(&dir)?)?);
+    }
+    let repo = trusted..found.len();
+    found.extend(templates_in(source::open(Path::new("templates"))?)?);
 
+    let mut templates: Vec<(TemplateManifest, TemplateLocation)> = Vec::new();
+    for (i, (m, location)) in found.into_iter().enumerate() {
+        match templates.iter().find(|(existing, _)| existing.id == m.id) {
+            None => templates.push((m, location)),
+            Some((_, existing)) if repo.contains(&i) => eprintln!(
+                "{} Ignoring template '{}' from {}: the one in {} has the same id",
+                "!".yellow(),
+                m.id,
+                location.source.describe(),
+                existing.source.describe()
+            ),
+            Some(_) => {}
+        }
+    }
+    Ok(templates)
+}
+
+/// Every template directory directly below the root of `source`.
+fn templates_in(source: Arc<dyn TemplateSource>) -> Result<Vec<(TemplateManifest, TemplateLocation)>> {
+    Ok(manifest::manifests(source.as_ref())?
+        .into_iter()
+        .map(|(m, path)| {
+            let location = TemplateLocation {
+                source: source.clone(),
+                path,
+            };
+            (m, location)
+        })
+        .collect())
+}
+
+/// The `.tar.gz`/`.tgz` archives directly inside `dir`, sorted by name.
+fn user_archives(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut archives: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.ends_with(".tar.gz") || n.ends_with(".tgz"))
+        })
+        .collect();
+    archives.sort();
+    Ok(archives)
+}
+
+/// Resolve `--template`: a git URL (`git+file:///srv/house.git#v2`), a path to a template
+/// directory or archive, or the id of an available template. `subdir` selects the
+/// template directory inside a git repository, directory or archive.
+pub fn find_template_by_name(
+    name: &str,
+    subdir: Option<&Path>,
+) -> Result<(TemplateManifest, TemplateLocation)> {
+    if let Some((url, reference)) = source::parse_git_spec(name) {
+        let git = GitSource::clone(&url, reference.as_deref())?;
+        println!(
+            "{} Cloned '{}' at {}",
+            "✓".green(),
+            url,
+            git.revision().unwrap_or_default()
+        );
+        return TemplateLocation::from_source(Arc::new(git), subdir);
+    }
+
+    let path = Path::new(name);
+    if path.exists() {
+        return TemplateLocation::from_path(path, subdir);
+    }
+    if subdir.is_some() {
+        anyhow::bail!("--subdir needs a template path or git URL, not a template id");
+    }
+
+    let templates = available_templates()?;
+    if let Some(found) = templates.iter().find(|(m, _)| m.id == name) {
+        return Ok(found.clone());
+    }
+    let mut ids: Vec<&str> = templates.iter().map(|(m, _)| m.id.as_str()).collect();
+    ids.sort();
+    anyhow::bail!(
+        "No template named '{}' and no such path.\nAvailable templates:\n  {}",
+        name,
+        ids.join("\n  ")
+    )
+}
+
+/// Find the template serving the given frontend/database pairing.
+pub fn find_template(
+    frontend: &str,
+    db: Option<&str>,
+) -> Result<(TemplateManifest, TemplateLocation)> {
+    let templates = available_templates()?;
+    if let Some(found) = templates.iter().find(|(m, _)| m.matches(frontend, db)) {
+        return Ok(found.clone());
+    }
+
+    let mut combos: Vec<String> = templates
+        .iter()
+        .map(|(m, _)| match &m.db {
+            Some(db) => format!("--frontend {} --db {}", m.frontend, db),
+            None => format!("--frontend {}", m.frontend),
+        })
+        .collect();
+    combos.sort();
+    combos.dedup();
+    anyhow::bail!(
+        "No template found for frontend '{}' with {}.\nAvailable combinations:\n  {}",
+        frontend,
+        db.map(|d| format!("database '{}'", d))
+            .unwrap_or_else(|| "no database".to_string()),
+        combos.join("\n  ")
+    )
+}
+
+/// Render the template at `location` and create a new project from it in `target_dir`.
+///
+/// The files are written to a staging directory next to `target_dir`, and `finish` runs
+/// in it (git init, hooks). Only when that succeeded is the staging directory renamed into
+/// place, so a failure at any step never leaves a half-populated project behind.
 pub fn scaffold_project(
     target_dir: &Path,
-    project_name: &str,
-    template_path: &Path,
+    context: &RenderContext,
+    location: &TemplateLocation,
+    finish: impl FnOnce(&Path) -> Result<()>,
 ) -> Result<()> {
     if target_dir.exists() {
         anyhow::bail!("Directory '{}' already exists.", target_dir.display());
     }
+    let project = render_project(context, location)?;
 
     println!(
         "{} Creating project in '{}'...",
         "✓".green(),
         target_dir.display()
     );
-
-    for entry in WalkDir::new(template_path).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        let relative_path = path.strip_prefix(template_path)?;
-        let target_path = target_dir.join(relative_path);
-
-        if path.is_dir() {
-            fs::create_dir_all(&target_path)
-                .with_context(|| format!("Failed to create directory: {}", target_path.display()))?;
-        } else {
-            let content_bytes = fs::read(path)
-                .with_context(|| format!("Failed to read template file: {}", path.display()))?;
-
-            let new_content = if let Ok(content_str) = String::from_utf8(content_bytes.clone()) {
-                content_str.replace(PLACEHOLDER, project_name).into_bytes()
-            } else {
-                content_bytes
-            };
-
-            fs::write(&target_path, new_content)
-                .with_context(|| format!("Failed to write file: {}", target_path.display()))?;
-        }
-    }
+    let staging = Staging::new(target_dir)?;
+    project.write_to(staging.path())?;
+    finish(staging.path())?;
+    staging.commit()?;
 
     println!(
         "{} {}",
@@ -52,118 +284,276 @@ pub fn scaffold_project(
     Ok(())
 }
 
-/// Scaffold a project from embedded templates using include_dir.
-/// This avoids relying on a runtime `templates/` directory, which is not shipped in crates.io installs.
-pub fn scaffold_project_embedded(
+/// Scaffold into a directory that already exists (e.g. a repository with a README and
+/// LICENSE), resolving every file that already exists with `strategy`.
+pub fn scaffold_into_existing(
     target_dir: &Path,
-    project_name: &str,
-    template_name: &str,
-    templates_root: &include_dir::Dir,
-) -> Result<()> {
-    if target_dir.exists() {
-        anyhow::bail!("Directory '{}' already exists.", target_dir.display());
-    }
+    context: &RenderContext,
+    location: &TemplateLocation,
+    strategy: ConflictStrategy,
+) -> Result<WriteSummary> {
+    let project = render_project(context, location)?;
 
     println!(
-        "{} Creating project in '{}'...",
+        "{} Writing into existing directory '{}'...",
         "✓".green(),
         target_dir.display()
     );
+    write_into_existing(&project, target_dir, strategy)
+}
 
-    let template_dir = templates_root
-        .get_dir(template_name)
-        .ok_or_else(|| anyhow::anyhow!("Template '{}' not found in embedded assets", template_name))?;
-
-    // Helper to recursively copy directories and files
-    fn copy_dir_recursive(
-        src: &include_dir::Dir,
-        root: &include_dir::Dir,
-        target_root: &Path,
-        project_name: &str,
-    ) -> Result<()> {
-        // Ensure this directory exists at destination
-        let rel = src.path().strip_prefix(root.path()).unwrap_or(src.path());
-        let dir_target = target_root.join(rel);
-        fs::create_dir_all(&dir_target)
-            .with_context(|| format!("Failed to create directory: {}", dir_target.display()))?;
-
-        // Files in this directory
-        for f in src.files() {
-            let rel_f = f.path().strip_prefix(root.path()).unwrap_or(f.path());
-            let target_path = target_root.join(rel_f);
-            if let Some(parent) = target_path.parent() { fs::create_dir_all(parent).ok(); }
-
-            let bytes = f.contents();
-            let new_content = match std::str::from_utf8(bytes) {
-                Ok(s) => s.replace(PLACEHOLDER, project_name).into_bytes(),
-                Err(_) => bytes.to_vec(),
-            };
-            fs::write(&target_path, new_content)
-                .with_context(|| format!("Failed to write file: {}", target_path.display()))?;
+/// Compare a generated project with what its template generated, as stored in
+/// `.trestle/base.tar.gz`. Without a stored render that matches `.trestle/project.toml`,
+/// files are compared with their recorded hashes instead, without a diff.
+pub fn project_drift(project_dir: &Path) -> Result<Drift> {
+    let lock = ProjectLock::read(project_dir)?;
+    let original = original_render(project_dir, &lock);
+    let base = match &original {
+        Some(project) => Base::Rendered(project),
+        None => {
+            println!(
+                "{} {} is missing or does not match {};\n  \
+                 comparing with the recorded file hashes.",
+                "!".yellow(),
+                BASE_FILE,
+                LOCK_FILE
+            );
+            Base::Hashes(&lock.files)
         }
+    };
+    Drift::compute(project_dir, &base)
+}
 
-        // Recurse into subdirectories
-        for d in src.dirs() {
-            copy_dir_recursive(d, root, target_root, project_name)?;
-        }
+/// Bring a generated project up to date with its template: `location`, or by default the
+/// latest version of the template recorded in `.trestle/project.toml`.
+///
+/// The difference from the render stored in `.trestle/base.tar.gz` to the new render is
+/// three-way merged into the project's files, and the stored render is replaced. Without
+/// a stored render that matches `.trestle/project.toml` (e.g. after `trestle init` kept
+/// some existing files), only files that are unchanged since they were generated can be
+/// updated cleanly.
+pub fn update_project(
+    project_dir: &Path,
+    location: Option<TemplateLocation>,
+    dry_run: bool,
+) -> Result<UpdateSummary> {
+    let lock = ProjectLock::read(project_dir)?;
+    let context = lock.context()?;
+    let location = match location {
+        Some(location) => location,
+        None => lock.template.locate()?,
+    };
+    if lockfile::layers_checksum(&template_layers(&location)?) == lock.template.checksum {
+        println!(
+            "{} Already up to date with template '{}'.",
+            "✓".green(),
+            lock.template.id
+        );
+        return Ok(UpdateSummary::default());
+    }
 
-        Ok(())
+    let original = original_render(project_dir, &lock);
+    if original.is_none() {
+        println!(
+            "{} {} is missing or does not match {};\n  \
+             files changed since generation are marked as conflicts.",
+            "!".yellow(),
+            BASE_FILE,
+            LOCK_FILE
+        );
     }
+    let base = match &original {
+        Some(project) => Base::Rendered(project),
+        None => Base::Hashes(&lock.files),
+    };
 
-    copy_dir_recursive(template_dir, template_dir, target_dir, project_name)?;
+    let theirs = render_project(&context, &location)?;
+    let update = Update::plan(project_dir, &base, &theirs)?;
+    if !dry_run {
+        println!(
+            "{} Updating '{}' from template '{}'...",
+            "✓".green(),
+            project_dir.display(),
+            lock.template.id
+        );
+        update.apply(project_dir)?;
+    }
+    Ok(update.summary)
+}
 
-    println!(
-        "{} {}",
-        "✓".green(),
-        "Project scaffolded successfully!".bold()
+/// What the template generated for the project in `project_dir`, as stored with it, if
+/// that still matches the hashes in the lockfile.
+fn original_render(project_dir: &Path, lock: &ProjectLock) -> Option<RenderedProject> {
+    let base = match lockfile::read_base(project_dir) {
+        Ok(base) => base,
+        Err(e) => {
+            println!("{} {:#}", "!".yellow(), e);
+            None
+        }
+    };
+    base.filter(|base| lock.describes(base))
+}
+
+/// Render and validate a template in memory without writing anything, including the
+/// `.trestle/project.toml` lockfile that records how the project was generated and the
+/// `.trestle/base.tar.gz` copy of the render that later updates merge from.
+pub fn render_project(context: &RenderContext, location: &TemplateLocation) -> Result<RenderedProject> {
+    let layers = template_layers(location)?;
+    let mut project = render_validated(context, &layers)?;
+    let lock = ProjectLock::new(template_record(location, &layers)?, context, &project)?;
+    let base = lockfile::pack_base(&project)?;
+    project.files.insert(
+        PathBuf::from(BASE_FILE),
+        RenderedFile {
+            contents: base,
+            layer: "trestle".to_string(),
+        },
     );
-    Ok(())
+    project.files.insert(
+        PathBuf::from(LOCK_FILE),
+        RenderedFile {
+            contents: lock.to_toml()?.into_bytes(),
+            layer: "trestle".to_string(),
+        },
+    );
+    Ok(project)
+}
+
+/// What the lockfile records about the template at `location`.
+fn template_record(location: &TemplateLocation, layers: &[Layer]) -> Result<TemplateRecord> {
+    let source = location.source.as_ref();
+    let manifest = if source.files_in(&location.path)?.iter().any(|p| p == Path::new(MANIFEST_FILE)) {
+        Some(manifest::read(source, &location.path)?)
+    } else {
+        None
+    };
+    let id = match &manifest {
+        Some(m) => m.id.clone(),
+        None => location
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "custom".to_string()),
+    };
+    let path = location.path.to_string_lossy().replace('\\', "/");
+    Ok(TemplateRecord {
+        id,
+        version: manifest.and_then(|m| m.version),
+        kind: source.kind().to_string(),
+        source: source.origin(),
+        path: (!path.is_empty()).then_some(path),
+        commit: source.revision(),
+        checksum: lockfile::layers_checksum(layers),
+    })
+}
+
+/// The layers named in the template's manifest, followed by the template's own files.
+///
+/// Layer paths are resolved relative to the root of the template's source. Layers the
+/// source does not contain are taken from the embedded templates, so user templates
+/// can build on the built-in `layers/base`, `layers/htmx`, etc.
+fn template_layers(location: &TemplateLocation) -> Result<Vec<Layer>> {
+    let source = location.source.as_ref();
+    let mut layers = Vec::new();
+    if source.files_in(&location.path)?.iter().any(|p| p == Path::new(MANIFEST_FILE)) {
+        let manifest = manifest::read(source, &location.path)?;
+        for name in &manifest.layers {
+            let mut layer = read_layer(source, name, Path::new(name))?;
+            if layer.files.is_empty() {
+                layer = read_layer(&EmbeddedSource::new(&TEMPLATES), name, Path::new(name))?;
+            }
+            if layer.files.is_empty() {
+                anyhow::bail!("Layer '{}' not found in {}", name, source.describe());
+            }
+            layers.push(layer);
+        }
+    }
+
+    let own_name = location
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| source.describe());
+    layers.push(read_layer(source, &own_name, &location.path)?);
+    Ok(layers)
+}
+
+/// Collect the files below `dir` in `source`, relative to `dir`. The manifest is not part
+/// of the generated project.
+fn read_layer(source: &dyn TemplateSource, name: &str, dir: &Path) -> Result<Layer> {
+    let mut files = Vec::new();
+    for relative_path in source.files_in(dir)? {
+        if relative_path == Path::new(MANIFEST_FILE) {
+            continue;
+        }
+        let bytes = source.read(&dir.join(&relative_path))?;
+        files.push((relative_path, bytes));
+    }
+    Ok(Layer { name: name.to_string(), files })
+}
+
+/// The crates the template at `location` pulls in when rendered with `context`, to check
+/// a package name against before anything is generated.
+pub fn template_dependencies(context: &RenderContext, location: &TemplateLocation) -> Result<Vec<String>> {
+    Ok(RenderedProject::compose(&template_layers(location)?, context)?.dependencies())
+}
+
+/// Render all layers in memory, fill in the package metadata, and validate the result
+/// before anything touches the disk.
+fn render_validated(context: &RenderContext, layers: &[Layer]) -> Result<RenderedProject> {
+    let mut project = RenderedProject::compose(layers, context)?;
+    metadata::apply(&mut project, context)?;
+    validate_package_name(&context.names().raw, &project.dependencies())?;
+    Ok(project)
 }
 
-//
-// --- START OF FIXES ---
-//
 #[cfg(test)]
 mod tests {
     use super::*;
-    use assert_fs::prelude::*; // Import traits for .child(), .write_str(), etc.
+    use assert_fs::prelude::*;
     use predicates::prelude::*;
+    use source::DirectorySource;
     use std::fs;
-    // We don't need `tempfile` anymore, so we remove it.
-    // use tempfile::tempdir; // <-- REMOVE THIS
+
+    /// The template directory at `path`, with layers resolved relative to its parent.
+    fn disk_location(path: &Path) -> TemplateLocation {
+        TemplateLocation {
+            source: Arc::new(DirectorySource::new(path.parent().unwrap())),
+            path: PathBuf::from(path.file_name().unwrap()),
+        }
+    }
 
     #[test]
     fn test_scaffold_project_copies_and_replaces() {
-        // 1. Setup a temporary template directory using assert_fs
+        // 1. Setup a temporary template directory
         let template_dir = assert_fs::TempDir::new().unwrap();
-        let template_file = template_dir.child("src/main.rs"); // <-- .child() now works
+        let template_file = template_dir.child("src/main.rs");
         template_file
             .write_str("fn main() { println!(\"__PROJECT_NAME__\"); }")
             .unwrap();
 
-        let template_binary_file = template_dir.child("icon.png"); // <-- .child() now works
+        let template_binary_file = template_dir.child("icon.png");
         template_binary_file
             .write_binary(b"\x89PNG\r\n\x1a\n")
             .unwrap();
 
-        // 2. Setup a temporary target directory using assert_fs
+        // 2. Setup a temporary target directory
         let target_root = assert_fs::TempDir::new().unwrap();
         let project_name = "test-runner";
-        let target_project_dir = target_root.child(project_name); // <-- .child() now works
+        let target_project_dir = target_root.child(project_name);
 
         // 3. Run the scaffolding function
-        // We pass the .path() of the ChildPath to the function
-        scaffold_project(target_project_dir.path(), project_name, template_dir.path()).unwrap();
+        let context = RenderContext::new(project_name);
+        scaffold_project(target_project_dir.path(), &context, &disk_location(template_dir.path()), |_| Ok(())).unwrap();
 
         // 4. Assert the files were created
-        let expected_file = target_project_dir.child("src/main.rs"); // <-- .child()
-        expected_file.assert(predicate::path::is_file()); // <-- .assert() now works
+        let expected_file = target_project_dir.child("src/main.rs");
+        expected_file.assert(predicate::path::is_file());
 
-        let expected_binary = target_project_dir.child("icon.png"); // <-- .child()
-        expected_binary.assert(predicate::path::is_file()); // <-- .assert() now works
+        let expected_binary = target_project_dir.child("icon.png");
+        expected_binary.assert(predicate::path::is_file());
 
         // 5. Assert the placeholder was replaced
-        // We use .path() to read the file
         let content = fs::read_to_string(expected_file.path()).unwrap();
         assert!(content.contains("fn main() { println!(\"test-runner\"); }"));
         assert!(!content.contains("__PROJECT_NAME__"));
@@ -173,25 +563,127 @@ mod tests {
         assert_eq!(binary_content, b"\x89PNG\r\n\x1a\n");
     }
 
+    #[test]
+    fn test_scaffold_project_stacks_manifest_layers() {
+        let templates = assert_fs::TempDir::new().unwrap();
+        templates
+            .child("layers/base/Cargo.toml")
+            .write_str("[package]\nname = \"{{ project_name }}\"\n\n[dependencies]\naxum = \"0.7.5\"\n")
+            .unwrap();
+        templates
+            .child("layers/base/src/main.rs")
+            .write_str("fn main() {}\n")
+            .unwrap();
+        templates
+            .child("layers/pg/Cargo.toml")
+            .write_str("[dependencies]\nsqlx = \"0.7.4\"\n")
+            .unwrap();
+        templates
+            .child("layers/pg/.env.example")
+            .write_str("DATABASE_URL=\"postgres://\"\n")
+            .unwrap();
+        templates
+            .child("stack/trestle-template.toml")
+            .write_str("id = \"stack\"\ndescription = \"\"\nfrontend = \"htmx\"\nlayers = [\"layers/base\", \"layers/pg\"]\n")
+            .unwrap();
+        templates
+            .child("stack/src/main.rs")
+            .write_str("fn main() { println!(\"stack\"); }\n")
+            .unwrap();
+
+        let target = assert_fs::TempDir::new().unwrap();
+        let project = target.child("layered");
+        scaffold_project(
+            project.path(),
+            &RenderContext::new("layered"),
+            &disk_location(&templates.path().join("stack")),
+            |_| Ok(()),
+        )
+        .unwrap();
+
+        let cargo_toml = fs::read_to_string(project.child("Cargo.toml").path()).unwrap();
+        assert!(cargo_toml.contains("name = \"layered\""));
+        assert!(cargo_toml.contains("axum = \"0.7.5\""));
+        assert!(cargo_toml.contains("sqlx = \"0.7.4\""));
+        project.child(".env.example").assert(predicate::path::is_file());
+        project
+            .child("src/main.rs")
+            .assert("fn main() { println!(\"stack\"); }\n");
+        project.child("trestle-template.toml").assert(predicate::path::missing());
+    }
+
+    #[test]
+    fn test_template_from_path_falls_back_to_embedded_layers() {
+        let template_dir = assert_fs::TempDir::new().unwrap();
+        template_dir
+            .child("trestle-template.toml")
+            .write_str("id = \"house\"\ndescription = \"\"\nfrontend = \"htmx\"\nlayers = [\"layers/base\", \"layers/htmx\"]\n")
+            .unwrap();
+        template_dir
+            .child("src/logging.rs")
+            .write_str("// {{ project_snake }} logging\n")
+            .unwrap();
+
+        let (manifest, location) = TemplateLocation::from_path(template_dir.path(), None).unwrap();
+        assert_eq!(manifest.id, "house");
+
+        let project = render_project(&RenderContext::new("house-app"), &location).unwrap();
+        assert!(project.files.contains_key(Path::new("Cargo.toml")));
+        assert!(project.files.contains_key(Path::new("src/main.rs")));
+        assert_eq!(
+            project.files[Path::new("src/logging.rs")].contents,
+            b"// house_app logging\n"
+        );
+        assert!(!project.files.contains_key(Path::new(MANIFEST_FILE)));
+
+        let empty = assert_fs::TempDir::new().unwrap();
+        let err = TemplateLocation::from_path(empty.path(), None).unwrap_err();
+        assert!(err.to_string().contains("No trestle-template.toml found"), "{}", err);
+    }
+
+    #[test]
+    fn test_scaffold_project_leaves_nothing_behind_on_write_failure() {
+        let template_dir = assert_fs::TempDir::new().unwrap();
+        template_dir.child("a").write_str("file").unwrap();
+        // `a/b` cannot be written because `a` is a file.
+        template_dir.child("{{ 'a' }}/b").write_str("nested").unwrap();
+
+        let target_root = assert_fs::TempDir::new().unwrap();
+        let target_project_dir = target_root.child("broken");
+        let result = scaffold_project(
+            target_project_dir.path(),
+            &RenderContext::new("broken"),
+            &disk_location(template_dir.path()),
+            |_| Ok(()),
+        );
+
+        assert!(result.is_err());
+        target_project_dir.assert(predicate::path::missing());
+        assert_eq!(fs::read_dir(target_root.path()).unwrap().count(), 0);
+    }
+
     #[test]
     fn test_scaffold_project_fails_if_exists() {
         // 1. Setup a target directory that already exists
-        let target_root = assert_fs::TempDir::new().unwrap(); // Use assert_fs
+        let target_root = assert_fs::TempDir::new().unwrap();
         let project_name = "already-exists";
-        let target_project_dir = target_root.child(project_name); // Use .child()
-        fs::create_dir_all(target_project_dir.path()).unwrap(); // Use .path()
+        let target_project_dir = target_root.child(project_name);
+        fs::create_dir_all(target_project_dir.path()).unwrap();
 
         // 2. Setup a dummy template dir
-        let template_dir = assert_fs::TempDir::new().unwrap(); // Use assert_fs
+        let template_dir = assert_fs::TempDir::new().unwrap();
 
         // 3. Run and assert failure
-        let result =
-            scaffold_project(target_project_dir.path(), project_name, template_dir.path());
-        
+        let result = scaffold_project(
+            target_project_dir.path(),
+            &RenderContext::new(project_name),
+            &disk_location(template_dir.path()),
+            |_| Ok(()),
+        );
+
         assert!(result.is_err());
         assert_eq!(
             result.err().unwrap().to_string(),
-            // Use .path() to get the display path
             format!("Directory '{}' already exists.", target_project_dir.path().display())
         );
     }
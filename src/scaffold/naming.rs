@@ -0,0 +1,64 @@
+use heck::{ToKebabCase, ToSnakeCase, ToTitleCase, ToUpperCamelCase};
+
+/// The case variants of a project name that templates can refer to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectNames {
+    /// The name exactly as the user typed it.
+    pub raw: String,
+    /// Rust crate identifier, e.g. `my_app` (`use my_app::app;`, wasm-bindgen output files).
+    pub snake: String,
+    /// Cargo package name, e.g. `my-app`.
+    pub kebab: String,
+    /// Type names, e.g. `MyApp`.
+    pub pascal: String,
+    /// Human readable title, e.g. `My App` (page `<title>`s, headings).
+    pub title: String,
+}
+
+impl ProjectNames {
+    pub fn new(name: &str) -> Self {
+        Self {
+            raw: name.to_string(),
+            snake: name.to_snake_case(),
+            kebab: name.to_kebab_case(),
+            pascal: name.to_upper_camel_case(),
+            title: name.to_title_case(),
+        }
+    }
+
+    /// Template variables and legacy `__token__` placeholders for each variant.
+    pub fn variables(&self) -> [(&'static str, &'static str, &str); 5] {
+        [
+            ("project_name", "__PROJECT_NAME__", &self.raw),
+            ("project_snake", "__project_snake__", &self.snake),
+            ("project_kebab", "__project_kebab__", &self.kebab),
+            ("project_pascal", "__project_pascal__", &self.pascal),
+            ("project_title", "__project_title__", &self.title),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_variants_of_hyphenated_name() {
+        let names = ProjectNames::new("my-dioxus-pg-app");
+        assert_eq!(names.snake, "my_dioxus_pg_app");
+        assert_eq!(names.kebab, "my-dioxus-pg-app");
+        assert_eq!(names.pascal, "MyDioxusPgApp");
+        assert_eq!(names.title, "My Dioxus Pg App");
+    }
+
+    #[test]
+    fn test_case_variants_of_snake_and_camel_names() {
+        let names = ProjectNames::new("my_static_site");
+        assert_eq!(names.kebab, "my-static-site");
+        assert_eq!(names.pascal, "MyStaticSite");
+
+        let names = ProjectNames::new("TodoApp2");
+        assert_eq!(names.snake, "todo_app2");
+        assert_eq!(names.title, "Todo App2");
+    }
+}
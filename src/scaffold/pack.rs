@@ -0,0 +1,254 @@
+use super::manifest::{self, TemplateManifest};
+use super::source::{ArchiveSource, DirectorySource, TemplateSource};
+use super::staging::Staging;
+use super::TemplateLocation;
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Suffix of the checksum file written next to a packed archive.
+pub const CHECKSUM_SUFFIX: &str = ".sha256";
+
+/// The result of [`pack`].
+#[derive(Debug)]
+pub struct PackedTemplate {
+    pub manifest: TemplateManifest,
+    pub archive: PathBuf,
+    pub sha256: String,
+}
+
+/// The result of [`install`].
+#[derive(Debug)]
+pub struct InstalledTemplate {
+    pub manifest: TemplateManifest,
+    pub dir: PathBuf,
+    /// Whether a `.sha256` file was found next to the archive and matched.
+    pub verified: bool,
+}
+
+/// Hex-encoded SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Pack the template in `template_dir` into a `.tar.gz` and write its checksum next to it.
+///
+/// The archive is written to `output`, or to `<id>-<version>.tar.gz` (`<id>.tar.gz`
+/// without a version) in the current directory. Entries are sorted and carry no
+/// timestamps or owners, so packing the same files twice gives the same checksum.
+pub fn pack(template_dir: &Path, output: Option<&Path>) -> Result<PackedTemplate> {
+    let source = DirectorySource::new(template_dir);
+    let manifest = manifest::read(&source, Path::new(""))
+        .with_context(|| format!("'{}' is not a template directory", template_dir.display()))?;
+
+    let archive = match output {
+        Some(path) => path.to_path_buf(),
+        None => PathBuf::from(match &manifest.version {
+            Some(version) => format!("{}-{}.tar.gz", manifest.id, version),
+            None => format!("{}.tar.gz", manifest.id),
+        }),
+    };
+    // Do not pack a previous archive written into the template directory itself.
+    let skip = archive
+        .canonicalize()
+        .ok()
+        .and_then(|a| a.strip_prefix(template_dir.canonicalize().ok()?).ok().map(Path::to_path_buf));
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for path in source.files()? {
+        if skip.as_deref() == Some(path.as_path())
+            || skip.as_deref().map(checksum_path).as_deref() == Some(path.as_path())
+        {
+            continue;
+        }
+        let contents = source.read(&path)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(file_mode(&template_dir.join(&path)));
+        header.set_mtime(0);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, &path, contents.as_slice())
+            .with_context(|| format!("Failed to pack '{}'", path.display()))?;
+    }
+    let bytes = builder.into_inner()?.finish()?;
+
+    let sha256 = sha256_hex(&bytes);
+    fs::write(&archive, &bytes)
+        .with_context(|| format!("Failed to write archive: {}", archive.display()))?;
+    let file_name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let checksum = checksum_path(&archive);
+    fs::write(&checksum, format!("{}  {}\n", sha256, file_name))
+        .with_context(|| format!("Failed to write checksum: {}", checksum.display()))?;
+
+    Ok(PackedTemplate {
+        manifest,
+        archive,
+        sha256,
+    })
+}
+
+/// Unpack the template archive into `templates_dir/<id>`.
+///
+/// When a `<archive>.sha256` file exists next to the archive, the archive must match it.
+/// An installed template with the same id is only replaced with `force`.
+pub fn install(archive: &Path, templates_dir: &Path, force: bool) -> Result<InstalledTemplate> {
+    let bytes = fs::read(archive)
+        .with_context(|| format!("Failed to read template archive: {}", archive.display()))?;
+    let checksum = checksum_path(archive);
+    let verified = checksum.is_file();
+    if verified {
+        let expected = fs::read_to_string(&checksum)
+            .with_context(|| format!("Failed to read checksum: {}", checksum.display()))?;
+        let expected = expected.split_whitespace().next().unwrap_or_default();
+        let actual = sha256_hex(&bytes);
+        if !expected.eq_ignore_ascii_case(&actual) {
+            anyhow::bail!(
+                "Checksum mismatch for '{}': expected {}, got {}",
+                archive.display(),
+                expected,
+                actual
+            );
+        }
+    }
+
+    let source: Arc<dyn TemplateSource> = Arc::new(ArchiveSource::open(archive)?);
+    let (manifest, location) = TemplateLocation::from_source(source.clone(), None)?;
+
+    let dir = templates_dir.join(&manifest.id);
+    if dir.exists() {
+        if !force {
+            anyhow::bail!(
+                "Template '{}' is already installed at '{}'. Use --force to replace it.",
+                manifest.id,
+                dir.display()
+            );
+        }
+        fs::remove_dir_all(&dir)
+            .with_context(|| format!("Failed to remove '{}'", dir.display()))?;
+    }
+
+    let staging = Staging::new(&dir)?;
+    for path in source.files_in(&location.path)? {
+        let target = staging.path().join(&path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(&target, source.read(&location.path.join(&path))?)
+            .with_context(|| format!("Failed to write file: {}", target.display()))?;
+    }
+    staging.commit()?;
+
+    Ok(InstalledTemplate {
+        manifest,
+        dir,
+        verified,
+    })
+}
+
+/// Remove the installed template `id` from `templates_dir`, returning its directory.
+pub fn remove(templates_dir: &Path, id: &str) -> Result<PathBuf> {
+    let Some((_, path)) = installed(templates_dir)?.into_iter().find(|(m, _)| m.id == id) else {
+        anyhow::bail!("No installed template named '{}'", id);
+    };
+    fs::remove_dir_all(&path).with_context(|| format!("Failed to remove '{}'", path.display()))?;
+    Ok(path)
+}
+
+/// Every template installed in `templates_dir`, with its directory.
+pub fn installed(templates_dir: &Path) -> Result<Vec<(TemplateManifest, PathBuf)>> {
+    let source = DirectorySource::new(templates_dir);
+    Ok(manifest::manifests(&source)?
+        .into_iter()
+        .map(|(m, path)| (m, templates_dir.join(path)))
+        .collect())
+}
+
+fn checksum_path(archive: &Path) -> PathBuf {
+    let mut path = archive.as_os_str().to_owned();
+    path.push(CHECKSUM_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// Keep the executable bit (e.g. for hook scripts); everything else is packed as 0644.
+fn file_mode(path: &Path) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0) {
+            return 0o755;
+        }
+    }
+    let _ = path;
+    0o644
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    fn house_template() -> assert_fs::TempDir {
+        let dir = assert_fs::TempDir::new().unwrap();
+        dir.child("trestle-template.toml")
+            .write_str("id = \"house\"\ndescription = \"House\"\nversion = \"1.2\"\nfrontend = \"htmx\"\n")
+            .unwrap();
+        dir.child("src/main.rs").write_str("fn main() {}\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_pack_is_reproducible_and_installs() {
+        let template = house_template();
+        let out = assert_fs::TempDir::new().unwrap();
+
+        let first = pack(template.path(), Some(&out.path().join("a.tar.gz"))).unwrap();
+        let second = pack(template.path(), Some(&out.path().join("b.tar.gz"))).unwrap();
+        assert_eq!(first.manifest.version.as_deref(), Some("1.2"));
+        assert_eq!(first.sha256, second.sha256);
+        out.child("a.tar.gz.sha256")
+            .assert(format!("{}  a.tar.gz\n", first.sha256));
+
+        let templates_dir = out.child("templates");
+        let result = install(&first.archive, templates_dir.path(), false).unwrap();
+        assert!(result.verified);
+        assert_eq!(result.dir, templates_dir.path().join("house"));
+        templates_dir.child("house/src/main.rs").assert("fn main() {}\n");
+        templates_dir
+            .child("house/trestle-template.toml")
+            .assert(predicates::path::is_file());
+
+        assert!(install(&first.archive, templates_dir.path(), false).is_err());
+        install(&first.archive, templates_dir.path(), true).unwrap();
+
+        let listed = installed(templates_dir.path()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].0.id, "house");
+
+        remove(templates_dir.path(), "house").unwrap();
+        templates_dir.child("house").assert(predicates::path::missing());
+        assert!(remove(templates_dir.path(), "house").is_err());
+    }
+
+    #[test]
+    fn test_install_rejects_checksum_mismatch() {
+        let template = house_template();
+        let out = assert_fs::TempDir::new().unwrap();
+        let packed = pack(template.path(), Some(&out.path().join("house.tar.gz"))).unwrap();
+        out.child("house.tar.gz.sha256")
+            .write_str(&format!("{}  house.tar.gz\n", "0".repeat(64)))
+            .unwrap();
+
+        let err = install(&packed.archive, &out.path().join("templates"), false).unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        out.child("templates/house").assert(predicates::path::missing());
+    }
+}
@@ -0,0 +1,247 @@
+use super::conflicts::merge_existing;
+use super::layers::{RenderedFile, RenderedProject};
+use anyhow::{Context, Result};
+use colored::*;
+use diffy::DiffOptions;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A directory level of the preview tree.
+#[derive(Default)]
+struct Node<'a> {
+    dirs: BTreeMap<String, Node<'a>>,
+    files: BTreeMap<String, &'a RenderedFile>,
+}
+
+/// Render the project as a tree, one line per file or directory, with the size and
+/// source layer of every file:
+///
+/// ```text
+/// my-app/
+/// ├── Cargo.toml    1.2 KiB  layers/htmx
+/// └── src/
+///     └── main.rs     812 B  layers/htmx
+/// ```
+pub fn tree(root: &str, project: &RenderedProject) -> String {
+    let mut top = Node::default();
+    for (path, file) in &project.files {
+        let mut node = &mut top;
+        let mut components: Vec<String> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let Some(name) = components.pop() else {
+            continue;
+        };
+        for dir in components {
+            node = node.dirs.entry(dir).or_default();
+        }
+        node.files.insert(name, file);
+    }
+
+    let mut rows = Vec::new();
+    collect_rows(&top, "", &mut rows);
+
+    let width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+    let mut out = format!("{}/\n", root);
+    for (label, file) in rows {
+        match file {
+            Some(file) => out.push_str(&format!(
+                "{:<width$}  {:>9}  {}\n",
+                label,
+                format_size(file.contents.len()),
+                file.layer,
+                width = width
+            )),
+            None => out.push_str(&format!("{}\n", label)),
+        }
+    }
+    let total: usize = project.files.values().map(|f| f.contents.len()).sum();
+    out.push_str(&format!("\n{} files, {}\n", project.files.len(), format_size(total)));
+    out
+}
+
+fn collect_rows<'a>(node: &Node<'a>, prefix: &str, rows: &mut Vec<(String, Option<&'a RenderedFile>)>) {
+    // Directories first, then files, each in name order.
+    let entries: Vec<(&String, Option<&Node>, Option<&RenderedFile>)> = node
+        .dirs
+        .iter()
+        .map(|(name, dir)| (name, Some(dir), None))
+        .chain(node.files.iter().map(|(name, file)| (name, None, Some(*file))))
+        .collect();
+
+    for (i, (name, dir, file)) in entries.iter().enumerate() {
+        let last = i + 1 == entries.len();
+        let branch = if last { "└── " } else { "├── " };
+        match dir {
+            Some(dir) => {
+                rows.push((format!("{}{}{}/", prefix, branch, name), None));
+                let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                collect_rows(dir, &child_prefix, rows);
+            }
+            None => rows.push((format!("{}{}{}", prefix, branch, name), *file)),
+        }
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Unified diff of what generating `project` into `dir` would change.
+///
+/// Files that are missing from `dir` show up as additions; files identical to the
+/// existing ones are left out. Existing `.gitignore` and `.env.example` files are
+/// compared with the merged lines that would be written into them, not replaced.
+/// Files in `dir` that the template does not produce are never touched, so they are
+/// not listed either.
+pub fn diff_against(project: &RenderedProject, dir: &Path) -> Result<String> {
+    let mut out = String::new();
+    for (path, file) in &project.files {
+        let existing_path = dir.join(path);
+        let existing = if existing_path.is_file() {
+            Some(
+                fs::read(&existing_path)
+                    .with_context(|| format!("Failed to read file: {}", existing_path.display()))?,
+            )
+        } else {
+            None
+        };
+        let new = existing
+            .as_deref()
+            .and_then(|existing| merge_existing(path, existing, &file.contents))
+            .unwrap_or_else(|| file.contents.clone());
+        if existing.as_deref() == Some(new.as_slice()) {
+            continue;
+        }
+
+        let original_name = match existing {
+            Some(_) => format!("a/{}", path.display()),
+            None => "/dev/null".to_string(),
+        };
+        let modified_name = format!("b/{}", path.display());
+        let old = existing.unwrap_or_default();
+        match (std::str::from_utf8(&old), std::str::from_utf8(&new)) {
+            (Ok(old), Ok(new)) => {
+                let patch = DiffOptions::new()
+                    .set_original_filename(original_name)
+                    .set_modified_filename(modified_name)
+                    .create_patch(old, new)
+                    .to_string();
+                out.push_str(&patch);
+            }
+            _ => out.push_str(&format!(
+                "Binary files {} and {} differ\n",
+                original_name, modified_name
+            )),
+        }
+    }
+    Ok(out)
+}
+
+/// Print a unified diff with the usual colours.
+pub fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        let line = if line.starts_with("+++") || line.starts_with("---") {
+            line.bold()
+        } else if line.starts_with('+') {
+            line.green()
+        } else if line.starts_with('-') {
+            line.red()
+        } else if line.starts_with("@@") {
+            line.cyan()
+        } else {
+            line.normal()
+        };
+        println!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn project(files: &[(&str, &str, &str)]) -> RenderedProject {
+        let mut project = RenderedProject::default();
+        for (path, contents, layer) in files {
+            project.files.insert(
+                PathBuf::from(path),
+                RenderedFile {
+                    contents: contents.as_bytes().to_vec(),
+                    layer: layer.to_string(),
+                },
+            );
+        }
+        project
+    }
+
+    #[test]
+    fn test_tree_lists_sizes_and_layers() {
+        let project = project(&[
+            ("Cargo.toml", "[package]\n", "layers/base"),
+            ("src/main.rs", "fn main() {}\n", "layers/htmx"),
+            ("src/views/mod.rs", "", "layers/htmx"),
+        ]);
+
+        assert_eq!(
+            tree("demo", &project),
+            "demo/\n\
+             ├── src/\n\
+             │   ├── views/\n\
+             │   │   └── mod.rs        0 B  layers/htmx\n\
+             │   └── main.rs          13 B  layers/htmx\n\
+             └── Cargo.toml           10 B  layers/base\n\
+             \n\
+             3 files, 23 B\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_against_existing_directory() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        fs::write(dir.path().join("README.md"), "# Demo\nold\n").unwrap();
+        fs::write(dir.path().join("same.txt"), "same\n").unwrap();
+        let project = project(&[
+            ("README.md", "# Demo\nnew\n", "base"),
+            ("same.txt", "same\n", "base"),
+            ("src/main.rs", "fn main() {}\n", "base"),
+        ]);
+
+        let diff = diff_against(&project, dir.path()).unwrap();
+        assert!(diff.contains("--- a/README.md\n+++ b/README.md\n"), "{}", diff);
+        assert!(diff.contains("-old\n+new\n"), "{}", diff);
+        assert!(diff.contains("--- /dev/null\n+++ b/src/main.rs\n"), "{}", diff);
+        assert!(!diff.contains("same.txt"), "{}", diff);
+    }
+
+    #[test]
+    fn test_diff_against_shows_merged_line_files() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        fs::write(dir.path().join(".gitignore"), "/target
+notes/
+").unwrap();
+        fs::write(dir.path().join(".env.example"), "DATABASE_URL=postgres://mine
+").unwrap();
+        let project = project(&[
+            (".gitignore", "/target
+", "base"),
+            (".env.example", "DATABASE_URL=postgres://localhost
+PORT=3000
+", "base"),
+        ]);
+
+        let diff = diff_against(&project, dir.path()).unwrap();
+        assert!(!diff.contains(".gitignore"), "{}", diff);
+        assert!(diff.contains("--- a/.env.example\n+++ b/.env.example\n"), "{}", diff);
+        assert!(diff.contains(" DATABASE_URL=postgres://mine\n+PORT=3000\n"), "{}", diff);
+        assert!(!diff.contains("-DATABASE_URL"), "{}", diff);
+    }
+}
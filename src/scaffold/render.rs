@@ -0,0 +1,302 @@
+use super::naming::ProjectNames;
+use anyhow::{Context, Result};
+use minijinja::{Environment, Value};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// Variables made available to templates while rendering.
+///
+/// Templates use Jinja syntax (`{{ project_name }}`, `{% if db %}...{% endif %}`,
+/// `{% for %}` loops). Wrap literal `{{`/`{%` sequences in `{% raw %}...{% endraw %}`.
+///
+/// The project name is available in several cases (`project_snake`, `project_kebab`,
+/// `project_pascal`, `project_title`), each also usable as a `__project_snake__`-style
+/// placeholder alongside the legacy `__PROJECT_NAME__`.
+#[derive(Debug, Clone)]
+pub struct RenderContext {
+    names: ProjectNames,
+    vars: BTreeMap<String, Value>,
+}
+
+impl RenderContext {
+    pub fn new(project_name: &str) -> Self {
+        let names = ProjectNames::new(project_name);
+        let mut vars = BTreeMap::new();
+        for (key, _, value) in names.variables() {
+            vars.insert(key.to_string(), Value::from(value));
+        }
+        vars.insert("author".to_string(), Value::from(""));
+        vars.insert("description".to_string(), Value::from(()));
+        vars.insert("repository".to_string(), Value::from(()));
+        vars.insert("frontend".to_string(), Value::from(()));
+        vars.insert("db".to_string(), Value::from(()));
+        vars.insert("addons".to_string(), Value::from_serialize(BTreeMap::<String, bool>::new()));
+        vars.insert(
+            "timestamp".to_string(),
+            Value::from(chrono::Utc::now().format("%Y%m%d%H%M%S").to_string()),
+        );
+        Self { names, vars }
+    }
+
+    /// Rebuild a context from [`RenderContext::variables`], e.g. as recorded in a project
+    /// lockfile.
+    pub fn from_variables(vars: &BTreeMap<String, Value>) -> Result<Self> {
+        let name = vars
+            .get("project_name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing variable 'project_name'"))?;
+        let mut context = Self::new(name);
+        for (key, value) in vars {
+            context.insert(key, value.clone());
+        }
+        Ok(context)
+    }
+
+    pub fn names(&self) -> &ProjectNames {
+        &self.names
+    }
+
+    /// Every variable except the case variants derived from `project_name`.
+    pub fn variables(&self) -> BTreeMap<String, Value> {
+        let derived: Vec<&str> = self.names.variables().iter().skip(1).map(|(key, _, _)| *key).collect();
+        self.vars
+            .iter()
+            .filter(|(key, _)| !derived.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.vars.get(key)
+    }
+
+    /// Set (or replace) a variable.
+    pub fn insert(&mut self, key: &str, value: impl Into<Value>) -> &mut Self {
+        self.vars.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Render a text template. `name` is only used in error messages.
+    pub fn render_str(&self, name: &str, content: &str) -> Result<String> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+
+        let rendered = env
+            .render_str(content, &self.vars)
+            .with_context(|| format!("Failed to render template: {}", name))?;
+        Ok(self.replace_placeholders(&rendered))
+    }
+
+    /// Substitute the `__PROJECT_NAME__`-style placeholders.
+    pub fn replace_placeholders(&self, content: &str) -> String {
+        let mut out = content.to_string();
+        for (_, token, value) in self.names.variables() {
+            if out.contains(token) {
+                out = out.replace(token, value);
+            }
+        }
+        out
+    }
+
+    /// Render every segment of a template path, e.g. `src/__project_snake__/mod.rs` or
+    /// `migrations/{{ timestamp }}_init.sql`. Segments named in [`DOTFILES`] become
+    /// dotfiles (`_gitignore` is written as `.gitignore`).
+    ///
+    /// Returns `None` when any segment renders to an empty string, which lets templates
+    /// include a file conditionally by name (`{% if addons.docker %}Dockerfile{% endif %}`).
+    pub fn render_path(&self, path: &Path) -> Result<Option<PathBuf>> {
+        let mut rendered = PathBuf::new();
+        for component in path.components() {
+            let Component::Normal(segment) = component else {
+                anyhow::bail!("Template path '{}' must be relative", path.display());
+            };
+            let segment = segment.to_string_lossy();
+            let segment = if segment.contains("{{") || segment.contains("{%") {
+                self.render_str(&path.display().to_string(), &segment)?
+            } else {
+                self.replace_placeholders(&segment)
+            };
+            let segment = segment.trim();
+            if segment.is_empty() {
+                return Ok(None);
+            }
+            // A variable must not add directories or climb out of the project (`../x`, `a/b`).
+            let mut parts = Path::new(segment).components();
+            if !matches!((parts.next(), parts.next()), (Some(Component::Normal(_)), None)) {
+                anyhow::bail!(
+                    "Template path '{}' renders the file name '{}', which is not a single path segment",
+                    path.display(),
+                    segment
+                );
+            }
+            rendered.push(dotfile_name(segment).unwrap_or_else(|| segment.to_string()));
+        }
+        Ok(Some(rendered))
+    }
+
+    /// Render file contents. Non-UTF-8 (binary) files are passed through untouched.
+    pub fn render_bytes(&self, name: &str, bytes: &[u8]) -> Result<Vec<u8>> {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(self.render_str(name, s)?.into_bytes()),
+            Err(_) => Ok(bytes.to_vec()),
+        }
+    }
+}
+
+/// Template file names written as dotfiles. Shipping `.gitignore` and friends inside a
+/// template directory changes how git and `cargo package` treat the template itself.
+pub const DOTFILES: &[&str] = &[
+    "_gitignore",
+    "_gitattributes",
+    "_dockerignore",
+    "_editorconfig",
+    "_env.example",
+];
+
+/// `.gitignore` for `_gitignore`, also with a suffix such as `_gitignore.trestle-append`.
+fn dotfile_name(segment: &str) -> Option<String> {
+    DOTFILES.iter().find_map(|name| {
+        let rest = segment.strip_prefix(name)?;
+        (rest.is_empty() || rest.starts_with('.')).then(|| format!(".{}", &segment[1..]))
+    })
+}
+
+/// Best-effort author for the render context, taken from `git config user.name` and
+/// `user.email` as `Name <email>`.
+pub fn detect_author() -> Option<String> {
+    let name = git_config("user.name")?;
+    Some(match git_config("user.email") {
+        Some(email) => format!("{} <{}>", name, email),
+        None => name,
+    })
+}
+
+fn git_config(key: &str) -> Option<String> {
+    let output = Command::new("git").args(["config", "--get", key]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_variables_conditionals_and_loops() {
+        let mut ctx = RenderContext::new("blog");
+        ctx.insert("db", "postgres");
+        ctx.insert("env", Value::from(vec!["A", "B"]));
+
+        let out = ctx
+            .render_str(
+                "t",
+                "name={{ project_name }}\n{% if db == \"postgres\" %}\nsqlx\n{% endif %}\n{% for e in env %}{{ e }};{% endfor %}\ndone\n",
+            )
+            .unwrap();
+        assert_eq!(out, "name=blog\nsqlx\nA;B;done\n");
+    }
+
+    #[test]
+    fn test_render_keeps_rust_braces_and_raw_blocks() {
+        let ctx = RenderContext::new("blog");
+        let out = ctx
+            .render_str(
+                "t",
+                "fn main() { println!(\"{}\", \"__PROJECT_NAME__\"); }\n{% raw %}{{ not_a_var }}{% endraw %} ok\n",
+            )
+            .unwrap();
+        assert_eq!(out, "fn main() { println!(\"{}\", \"blog\"); }\n{{ not_a_var }} ok\n");
+    }
+
+    #[test]
+    fn test_render_case_variants() {
+        let ctx = RenderContext::new("my-dioxus-pg-app");
+        let out = ctx
+            .render_str(
+                "t",
+                "use {{ project_snake }}::app;\n<title>__project_title__</title>\n./pkg/__project_snake___bg.wasm\n",
+            )
+            .unwrap();
+        assert_eq!(
+            out,
+            "use my_dioxus_pg_app::app;\n<title>My Dioxus Pg App</title>\n./pkg/my_dioxus_pg_app_bg.wasm\n"
+        );
+    }
+
+    #[test]
+    fn test_render_path_segments() {
+        let mut ctx = RenderContext::new("my-app");
+        ctx.insert("timestamp", "20240101120000");
+        ctx.insert("db", Value::from(()));
+
+        assert_eq!(
+            ctx.render_path(Path::new("src/__project_snake__/mod.rs")).unwrap(),
+            Some(PathBuf::from("src/my_app/mod.rs"))
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("migrations/{{ timestamp }}_init.sql")).unwrap(),
+            Some(PathBuf::from("migrations/20240101120000_init.sql"))
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("src/{% if db %}db.rs{% endif %}")).unwrap(),
+            None
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("_gitignore")).unwrap(),
+            Some(PathBuf::from(".gitignore"))
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("_gitignore.trestle-append")).unwrap(),
+            Some(PathBuf::from(".gitignore.trestle-append"))
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("src/_gitignore_rules.rs")).unwrap(),
+            Some(PathBuf::from("src/_gitignore_rules.rs"))
+        );
+    }
+
+    #[test]
+    fn test_render_path_writes_env_example_as_dotfile() {
+        let ctx = RenderContext::new("my-app");
+        assert_eq!(
+            ctx.render_path(Path::new("_env.example")).unwrap(),
+            Some(PathBuf::from(".env.example"))
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("_env.example.trestle-append")).unwrap(),
+            Some(PathBuf::from(".env.example.trestle-append"))
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("_env.example.bak")).unwrap(),
+            Some(PathBuf::from(".env.example.bak"))
+        );
+        assert_eq!(
+            ctx.render_path(Path::new("_env.examples")).unwrap(),
+            Some(PathBuf::from("_env.examples"))
+        );
+    }
+
+    #[test]
+    fn test_render_path_rejects_segments_leaving_their_directory() {
+        let mut ctx = RenderContext::new("my-app");
+        ctx.insert("module", "../escape").insert("nested", "a/b").insert("dot", ".");
+
+        for template in ["src/{{ module }}.rs", "src/{{ nested }}.rs", "{{ dot }}/main.rs", "src/{{ module }}"] {
+            let err = ctx.render_path(Path::new(template)).unwrap_err();
+            assert!(err.to_string().contains("not a single path segment"), "{}: {}", template, err);
+        }
+    }
+
+    #[test]
+    fn test_render_bytes_passes_binary_through() {
+        let ctx = RenderContext::new("blog");
+        let png = b"\x89PNG\r\n\x1a\n{{ project_name }}";
+        assert_eq!(ctx.render_bytes("icon.png", png).unwrap(), png.to_vec());
+    }
+}
@@ -0,0 +1,503 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+/// A tree of template files, wherever it is stored.
+///
+/// Paths are always relative to the root of the source and use no `.`/`..`
+/// components. Everything above this trait (manifest discovery, layering,
+/// rendering, writing) is shared by every kind of source.
+pub trait TemplateSource: Debug + Send + Sync {
+    /// Human-readable description used in messages (e.g. `embedded`, a path).
+    fn describe(&self) -> String;
+
+    /// The kind of source: `embedded`, `directory`, `archive` or `git`.
+    fn kind(&self) -> &'static str;
+
+    /// Where the source can be found again: `embedded`, an absolute path, or a git URL.
+    fn origin(&self) -> String {
+        self.describe()
+    }
+
+    /// Every file in the source, sorted by path.
+    fn files(&self) -> Result<Vec<PathBuf>>;
+
+    /// The contents of the file at `path`.
+    fn read(&self, path: &Path) -> Result<Vec<u8>>;
+
+    /// The exact revision the files were read from, for sources that have one
+    /// (the resolved commit of a git template).
+    fn revision(&self) -> Option<String> {
+        None
+    }
+
+    /// The files below `dir`, relative to `dir`, sorted by path.
+    fn files_in(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .files()?
+            .into_iter()
+            .filter_map(|path| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+            .collect())
+    }
+}
+
+/// Open a template source stored at `path`: a `.tar.gz`/`.tgz` archive or a directory.
+pub fn open(path: &Path) -> Result<Arc<dyn TemplateSource>> {
+    if path.is_file() {
+        Ok(Arc::new(ArchiveSource::open(path)?))
+    } else {
+        Ok(Arc::new(DirectorySource::new(path)))
+    }
+}
+
+/// Templates compiled into the binary with `include_dir`.
+#[derive(Debug)]
+pub struct EmbeddedSource {
+    root: &'static include_dir::Dir<'static>,
+}
+
+impl EmbeddedSource {
+    pub fn new(root: &'static include_dir::Dir<'static>) -> Self {
+        Self { root }
+    }
+}
+
+impl TemplateSource for EmbeddedSource {
+    fn describe(&self) -> String {
+        "embedded templates".to_string()
+    }
+
+    fn kind(&self) -> &'static str {
+        "embedded"
+    }
+
+    fn origin(&self) -> String {
+        "embedded".to_string()
+    }
+
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        fn collect(dir: &include_dir::Dir, root: &Path, files: &mut Vec<PathBuf>) {
+            for f in dir.files() {
+                files.push(f.path().strip_prefix(root).unwrap_or(f.path()).to_path_buf());
+            }
+            for d in dir.dirs() {
+                collect(d, root, files);
+            }
+        }
+
+        let mut files = Vec::new();
+        collect(self.root, self.root.path(), &mut files);
+        files.sort();
+        Ok(files)
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        self.root
+            .get_file(self.root.path().join(path))
+            .map(|f| f.contents().to_vec())
+            .ok_or_else(|| anyhow::anyhow!("'{}' not found in embedded templates", path.display()))
+    }
+}
+
+/// Templates in a directory on disk. A missing directory is an empty source.
+#[derive(Debug)]
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl TemplateSource for DirectorySource {
+    fn describe(&self) -> String {
+        self.root.display().to_string()
+    }
+
+    fn kind(&self) -> &'static str {
+        "directory"
+    }
+
+    fn origin(&self) -> String {
+        absolute(&self.root)
+    }
+
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in WalkDir::new(&self.root).sort_by_file_name() {
+            let entry = entry
+                .with_context(|| format!("Failed to read template directory: {}", self.root.display()))?;
+            if entry.file_type().is_file() {
+                files.push(entry.path().strip_prefix(&self.root)?.to_path_buf());
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let full = self.root.join(path);
+        fs::read(&full).with_context(|| format!("Failed to read template file: {}", full.display()))
+    }
+}
+
+/// Templates packed into a gzip-compressed tarball, loaded into memory when opened.
+#[derive(Debug)]
+pub struct ArchiveSource {
+    path: PathBuf,
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl ArchiveSource {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("Failed to open template archive: {}", path.display()))?;
+        Self::from_reader(path, file)
+            .with_context(|| format!("Failed to read template archive: {}", path.display()))
+    }
+
+    fn from_reader(path: &Path, reader: impl Read) -> Result<Self> {
+        let mut archive = tar::Archive::new(GzDecoder::new(reader));
+        let mut files = BTreeMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let entry_path = normalize(&entry.path()?)?;
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            files.insert(entry_path, contents);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            files,
+        })
+    }
+}
+
+impl TemplateSource for ArchiveSource {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn kind(&self) -> &'static str {
+        "archive"
+    }
+
+    fn origin(&self) -> String {
+        absolute(&self.path)
+    }
+
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.files.keys().cloned().collect())
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            anyhow::anyhow!("'{}' not found in archive {}", path.display(), self.path.display())
+        })
+    }
+}
+
+/// A template repository cloned from git into a temporary directory, pinned to a ref.
+#[derive(Debug)]
+pub struct GitSource {
+    url: String,
+    commit: String,
+    checkout: DirectorySource,
+    // Removed when the source is dropped.
+    _dir: TempDir,
+}
+
+impl GitSource {
+    /// Clone `url` and check out `reference` (a tag, branch or commit; the default
+    /// branch when `None`).
+    pub fn clone(url: &str, reference: Option<&str>) -> Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("trestle-template-")
+            .tempdir()
+            .context("Failed to create a temporary directory")?;
+        let checkout = dir.path().join("checkout");
+
+        // `--` keeps a URL such as `--upload-pack=...` from being read as an option.
+        git(None, &["clone", "--quiet", "--no-checkout", "--", url, &checkout.to_string_lossy()])
+            .with_context(|| format!("Failed to clone template repository '{}'", url))?;
+
+        let commit = match reference {
+            Some(reference) => resolve_ref(&checkout, reference).with_context(|| {
+                format!("No tag, branch or commit '{}' in '{}'", reference, url)
+            })?,
+            None => git(Some(&checkout), &["rev-parse", "HEAD"])?,
+        };
+        git(Some(&checkout), &["checkout", "--quiet", "--detach", &commit])?;
+        // The history is not part of the template.
+        fs::remove_dir_all(checkout.join(".git"))
+            .with_context(|| format!("Failed to clean up clone of '{}'", url))?;
+
+        Ok(Self {
+            url: url.to_string(),
+            commit,
+            checkout: DirectorySource::new(checkout),
+            _dir: dir,
+        })
+    }
+}
+
+impl TemplateSource for GitSource {
+    fn describe(&self) -> String {
+        format!("{}#{}", self.url, self.commit)
+    }
+
+    fn kind(&self) -> &'static str {
+        "git"
+    }
+
+    fn origin(&self) -> String {
+        self.url.clone()
+    }
+
+    fn revision(&self) -> Option<String> {
+        Some(self.commit.clone())
+    }
+
+    fn files(&self) -> Result<Vec<PathBuf>> {
+        self.checkout.files()
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        self.checkout.read(path)
+    }
+}
+
+/// Split a git template spec into its clone URL and optional ref.
+///
+/// Accepts `git+<url>[#ref]` (e.g. `git+file:///srv/house.git#v2`) as well as plain
+/// git URLs: `*.git` URLs, `ssh://...` and `git@host:path`. Anything else is `None`.
+pub fn parse_git_spec(spec: &str) -> Option<(String, Option<String>)> {
+    let (url, reference) = match spec.split_once('#') {
+        Some((url, reference)) if !reference.is_empty() => (url, Some(reference.to_string())),
+        Some((url, _)) => (url, None),
+        None => (spec, None),
+    };
+    let url = if let Some(stripped) = url.strip_prefix("git+") {
+        stripped
+    } else if (url.ends_with(".git") && url.contains("://"))
+        || url.starts_with("ssh://")
+        || url.starts_with("git://")
+        || url.starts_with("git@")
+    {
+        url
+    } else {
+        return None;
+    };
+    Some((url.to_string(), reference))
+}
+
+/// The full commit hash `reference` points to, trying remote branches as well.
+fn resolve_ref(repo: &Path, reference: &str) -> Result<String> {
+    if reference.starts_with('-') {
+        anyhow::bail!("'{}' is not a tag, branch or commit", reference);
+    }
+    let candidates = [
+        format!("{}^{{commit}}", reference),
+        format!("origin/{}^{{commit}}", reference),
+    ];
+    for candidate in &candidates {
+        if let Ok(commit) = git(Some(repo), &["rev-parse", "--verify", "--quiet", candidate]) {
+            return Ok(commit);
+        }
+    }
+    anyhow::bail!("unknown revision '{}'", reference)
+}
+
+/// Run git non-interactively and return its trimmed stdout.
+pub(super) fn git(dir: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    let output = command
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .context("Failed to run git; is it installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn absolute(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Strip `./` prefixes and refuse entries that would escape the archive root.
+fn normalize(path: &Path) -> Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            _ => anyhow::bail!("Unsafe path in template archive: {}", path.display()),
+        }
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn tarball(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_directory_and_archive_sources_agree() {
+        let files = [("stack/trestle-template.toml", "id = \"stack\"\n"), ("layers/base/src/main.rs", "fn main() {}\n")];
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        for (path, contents) in files {
+            dir.child(path).write_str(contents).unwrap();
+        }
+        let archive = dir.child("templates.tar.gz");
+        let prefixed: Vec<_> = files.iter().map(|(p, c)| (format!("./{}", p), *c)).collect();
+        let prefixed: Vec<_> = prefixed.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+        archive.write_binary(&tarball(&prefixed)).unwrap();
+
+        let archive = open(archive.path()).unwrap();
+        let directory = DirectorySource::new(dir.path());
+        let expected = vec![
+            PathBuf::from("layers/base/src/main.rs"),
+            PathBuf::from("stack/trestle-template.toml"),
+        ];
+        assert_eq!(archive.files().unwrap(), expected);
+        assert_eq!(
+            directory.files().unwrap().into_iter().filter(|p| p.extension() != Some("gz".as_ref())).collect::<Vec<_>>(),
+            expected
+        );
+        for source in [archive.as_ref(), &directory as &dyn TemplateSource] {
+            assert_eq!(source.files_in(Path::new("layers/base")).unwrap(), vec![PathBuf::from("src/main.rs")]);
+            assert_eq!(source.read(Path::new("layers/base/src/main.rs")).unwrap(), b"fn main() {}\n");
+        }
+    }
+
+    #[test]
+    fn test_archive_rejects_escaping_paths() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let archive = dir.child("evil.tar.gz");
+        let mut header = tar::Header::new_gnu();
+        header.set_size(1);
+        header.set_mode(0o644);
+        // `append_data` refuses `..`, so write the raw name into the header.
+        header.as_gnu_mut().unwrap().name[..9].copy_from_slice(b"../escape");
+        header.set_cksum();
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        builder.append(&header, &b"x"[..]).unwrap();
+        archive.write_binary(&builder.into_inner().unwrap().finish().unwrap()).unwrap();
+
+        let err = ArchiveSource::open(archive.path()).unwrap_err();
+        assert!(format!("{:#}", err).contains("Unsafe path"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_parse_git_spec() {
+        assert_eq!(
+            parse_git_spec("git+file:///srv/templates/house.git#v2"),
+            Some(("file:///srv/templates/house.git".to_string(), Some("v2".to_string())))
+        );
+        assert_eq!(
+            parse_git_spec("https://example.com/house.git"),
+            Some(("https://example.com/house.git".to_string(), None))
+        );
+        assert_eq!(
+            parse_git_spec("git@example.com:team/house.git#main"),
+            Some(("git@example.com:team/house.git".to_string(), Some("main".to_string())))
+        );
+        assert_eq!(parse_git_spec("./house"), None);
+        assert_eq!(parse_git_spec("postgres-htmx"), None);
+    }
+
+    #[test]
+    fn test_git_source_checks_out_pinned_ref() {
+        let repo = assert_fs::TempDir::new().unwrap();
+        let run = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(status.status.success(), "{:?}", status);
+        };
+        run(&["init", "--quiet", "--initial-branch=main"]);
+        repo.child("house/README.md").write_str("v1\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "--quiet", "-m", "v1"]);
+        run(&["tag", "v1"]);
+        let v1 = git(Some(repo.path()), &["rev-parse", "HEAD"]).unwrap();
+        repo.child("house/README.md").write_str("v2\n").unwrap();
+        run(&["commit", "--quiet", "-am", "v2"]);
+
+        let url = format!("file://{}", repo.path().display());
+        let pinned = GitSource::clone(&url, Some("v1")).unwrap();
+        assert_eq!(pinned.revision(), Some(v1));
+        assert_eq!(pinned.read(Path::new("house/README.md")).unwrap(), b"v1\n");
+        assert_eq!(pinned.files().unwrap(), vec![PathBuf::from("house/README.md")]);
+
+        let latest = GitSource::clone(&url, None).unwrap();
+        assert_eq!(latest.read(Path::new("house/README.md")).unwrap(), b"v2\n");
+        let branch = GitSource::clone(&url, Some("main")).unwrap();
+        assert_eq!(branch.revision(), latest.revision());
+
+        assert!(GitSource::clone(&url, Some("nope")).is_err());
+        assert!(GitSource::clone(&url, Some("--output=/dev/null")).is_err());
+
+        let marker = repo.path().join("upload-pack-ran");
+        let option = format!("--upload-pack=touch {}", marker.display());
+        assert!(GitSource::clone(&option, None).is_err());
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_embedded_source_lists_bundled_templates() {
+        let source = EmbeddedSource::new(&crate::scaffold::TEMPLATES);
+        let files = source.files().unwrap();
+        assert!(files.contains(&PathBuf::from("layers/base/Cargo.toml")));
+        assert!(files.contains(&PathBuf::from("static-htmx/trestle-template.toml")));
+        assert!(!source.read(Path::new("static-htmx/trestle-template.toml")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+
+/// Staging directories, with the parent directories created for them, that must be
+/// removed if the process is interrupted.
+static ACTIVE: Mutex<Vec<(PathBuf, Vec<PathBuf>)>> = Mutex::new(Vec::new());
+static INSTALL_HANDLER: Once = Once::new();
+
+/// A temporary directory next to the target that a project is generated into.
+///
+/// Nothing appears at the target path until [`Staging::commit`] renames the
+/// staging directory into place. If the staging directory is dropped without
+/// being committed (an error was returned, or the user pressed Ctrl-C) it is removed,
+/// along with any parent directories that were created for it.
+#[derive(Debug)]
+pub struct Staging {
+    dir: PathBuf,
+    target: PathBuf,
+    /// Parent directories that did not exist before, deepest first.
+    created_parents: Vec<PathBuf>,
+    committed: bool,
+}
+
+impl Staging {
+    pub fn new(target: &Path) -> Result<Self> {
+        let parent = match target.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let created_parents: Vec<PathBuf> = parent
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+            .map(Path::to_path_buf)
+            .collect();
+        fs::create_dir_all(&parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+
+        let name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "project".to_string());
+        let dir = parent.join(format!(".{}.trestle-staging-{}", name, std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to remove stale staging directory: {}", dir.display()))?;
+        }
+        if let Err(e) = fs::create_dir(&dir) {
+            remove(&dir, &created_parents);
+            return Err(e).with_context(|| format!("Failed to create staging directory: {}", dir.display()));
+        }
+
+        install_interrupt_handler();
+        ACTIVE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((dir.clone(), created_parents.clone()));
+
+        Ok(Self {
+            dir,
+            target: target.to_path_buf(),
+            created_parents,
+            committed: false,
+        })
+    }
+
+    /// Directory to write the generated files into.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Move the staged project into place at the target path.
+    pub fn commit(mut self) -> Result<()> {
+        if self.target.exists() {
+            anyhow::bail!("Directory '{}' already exists.", self.target.display());
+        }
+        fs::rename(&self.dir, &self.target).with_context(|| {
+            format!(
+                "Failed to move '{}' into place at '{}'",
+                self.dir.display(),
+                self.target.display()
+            )
+        })?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        if !self.committed {
+            remove(&self.dir, &self.created_parents);
+        }
+        ACTIVE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(d, _)| d != &self.dir);
+    }
+}
+
+/// Remove a staging directory and then the parents created for it, as long as nothing
+/// else was put into them in the meantime.
+fn remove(dir: &Path, created_parents: &[PathBuf]) {
+    let _ = fs::remove_dir_all(dir);
+    for parent in created_parents {
+        if fs::remove_dir(parent).is_err() {
+            break;
+        }
+    }
+}
+
+/// On Ctrl-C, remove any staging directories before exiting.
+fn install_interrupt_handler() {
+    INSTALL_HANDLER.call_once(|| {
+        let _ = ctrlc::set_handler(|| {
+            for (dir, created_parents) in ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).drain(..) {
+                remove(&dir, &created_parents);
+            }
+            std::process::exit(130);
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_moves_staged_files_into_place() {
+        let root = assert_fs::TempDir::new().unwrap();
+        let target = root.path().join("nested/app");
+
+        let staging = Staging::new(&target).unwrap();
+        fs::write(staging.path().join("Cargo.toml"), "[package]").unwrap();
+        assert!(!target.exists());
+        staging.commit().unwrap();
+
+        assert!(target.join("Cargo.toml").is_file());
+        assert_eq!(fs::read_dir(root.path().join("nested")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_dropping_without_commit_removes_staging() {
+        let root = assert_fs::TempDir::new().unwrap();
+        let target = root.path().join("app");
+
+        let staging = Staging::new(&target).unwrap();
+        let staged = staging.path().to_path_buf();
+        fs::write(staged.join("half-written.rs"), "fn").unwrap();
+        drop(staging);
+
+        assert!(!staged.exists());
+        assert!(!target.exists());
+        assert_eq!(fs::read_dir(root.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_dropping_without_commit_removes_created_parents() {
+        let root = assert_fs::TempDir::new().unwrap();
+        fs::create_dir(root.path().join("existing")).unwrap();
+        let target = root.path().join("existing/new/deeper/app");
+
+        let staging = Staging::new(&target).unwrap();
+        assert!(root.path().join("existing/new/deeper").is_dir());
+        drop(staging);
+
+        assert!(!root.path().join("existing/new").exists());
+        assert!(root.path().join("existing").is_dir());
+    }
+}
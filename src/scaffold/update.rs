@@ -0,0 +1,272 @@
+use super::conflicts::{write_file, SIDE_SUFFIX};
+use super::layers::RenderedProject;
+use super::lockfile::{BASE_FILE, LOCK_FILE};
+use super::pack::sha256_hex;
+use anyhow::{Context, Result};
+use colored::*;
+use diffy::{ConflictStyle, MergeOptions};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What the project looked like when it was generated (or last updated).
+pub enum Base<'a> {
+    /// The old template rendered again with the recorded variables.
+    Rendered(&'a RenderedProject),
+    /// Only the recorded file hashes, when the old template is no longer available. Files
+    /// that still match their hash are known to be untouched; for the others the original
+    /// contents are unknown and every difference is reported as a conflict.
+    Hashes(&'a BTreeMap<String, String>),
+}
+
+impl Base<'_> {
+    /// Every generated path.
+    pub(super) fn paths(&self) -> BTreeSet<PathBuf> {
+        match self {
+            Base::Rendered(project) => project.files.keys().cloned().collect(),
+            Base::Hashes(hashes) => hashes.keys().map(PathBuf::from).collect(),
+        }
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        match self {
+            Base::Rendered(project) => project.files.contains_key(path),
+            Base::Hashes(hashes) => hashes.contains_key(&lock_key(path)),
+        }
+    }
+
+    /// The generated contents of `path`, if known. `ours` is the file as it is now.
+    pub(super) fn contents(&self, path: &Path, ours: Option<&[u8]>) -> Option<Vec<u8>> {
+        match self {
+            Base::Rendered(project) => project.files.get(path).map(|f| f.contents.clone()),
+            Base::Hashes(hashes) => {
+                let ours = ours?;
+                (hashes.get(&lock_key(path)) == Some(&sha256_hex(ours))).then(|| ours.to_vec())
+            }
+        }
+    }
+}
+
+/// What `trestle update` did (or would do) to each file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UpdateSummary {
+    /// Untouched files replaced by the new template version.
+    pub updated: Vec<PathBuf>,
+    /// Files changed in both the project and the template, merged without conflicts.
+    pub merged: Vec<PathBuf>,
+    /// Files written with conflict markers (or a `.trestle-new` side file for binaries).
+    pub conflicted: Vec<PathBuf>,
+    /// Files new in the template.
+    pub added: Vec<PathBuf>,
+    /// Untouched files the template no longer generates.
+    pub removed: Vec<PathBuf>,
+    /// Files the template changed that were deleted from the project; left deleted.
+    pub deleted_by_user: Vec<PathBuf>,
+    /// Modified files the template no longer generates; left in place.
+    pub kept: Vec<PathBuf>,
+}
+
+impl UpdateSummary {
+    pub fn print(&self) {
+        let sections = [
+            ("Updated", &self.updated, "~".green()),
+            ("Merged", &self.merged, "~".cyan()),
+            ("Conflicts (resolve the <<<<<<< markers)", &self.conflicted, "!".red()),
+            ("Added", &self.added, "+".green()),
+            ("Removed", &self.removed, "-".green()),
+            ("Deleted in project (left deleted)", &self.deleted_by_user, "-".dimmed()),
+            ("Modified but dropped from template (kept)", &self.kept, "?".yellow()),
+        ];
+        for (title, paths, marker) in sections {
+            if paths.is_empty() {
+                continue;
+            }
+            println!("\n{} ({}):", title.bold(), paths.len());
+            for path in paths {
+                println!("   {} {}", marker, path.display());
+            }
+        }
+    }
+}
+
+/// The changes needed to bring a project up to date with a new template render.
+#[derive(Debug, Default)]
+pub struct Update {
+    pub summary: UpdateSummary,
+    /// New contents per path, relative to the project root; `None` deletes the file.
+    pub changes: BTreeMap<PathBuf, Option<Vec<u8>>>,
+}
+
+impl Update {
+    /// Three-way merge the change from `base` to `theirs` (the new template render) into
+    /// the files of `project_dir`.
+    pub fn plan(project_dir: &Path, base: &Base, theirs: &RenderedProject) -> Result<Self> {
+        let mut update = Self::default();
+        let mut paths = base.paths();
+        paths.extend(theirs.files.keys().cloned());
+
+        for path in paths {
+            let target = project_dir.join(&path);
+            let ours = if target.is_file() {
+                Some(fs::read(&target).with_context(|| format!("Failed to read file: {}", target.display()))?)
+            } else {
+                None
+            };
+            let theirs = theirs.files.get(&path).map(|f| f.contents.as_slice());
+
+            // The lockfile and the stored render always describe the new render.
+            if path == Path::new(LOCK_FILE) || path == Path::new(BASE_FILE) {
+                if let Some(theirs) = theirs {
+                    update.changes.insert(path, Some(theirs.to_vec()));
+                }
+                continue;
+            }
+
+            let generated = base.contains(&path);
+            let original = base.contents(&path, ours.as_deref());
+            match (theirs, ours) {
+                (Some(theirs), None) => {
+                    if !generated {
+                        update.changes.insert(path.clone(), Some(theirs.to_vec()));
+                        update.summary.added.push(path);
+                    } else if original.as_deref() != Some(theirs) {
+                        update.summary.deleted_by_user.push(path);
+                    }
+                }
+                (Some(theirs), Some(ours)) => {
+                    if ours == theirs || original.as_deref() == Some(theirs) {
+                        continue;
+                    }
+                    if original.as_deref() == Some(ours.as_slice()) {
+                        update.changes.insert(path.clone(), Some(theirs.to_vec()));
+                        update.summary.updated.push(path);
+                        continue;
+                    }
+                    update.merge(&path, original.as_deref().unwrap_or_default(), &ours, theirs);
+                }
+                (None, Some(ours)) => {
+                    if original.as_deref() == Some(ours.as_slice()) {
+                        update.changes.insert(path.clone(), None);
+                        update.summary.removed.push(path);
+                    } else if generated {
+                        update.summary.kept.push(path);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        Ok(update)
+    }
+
+    fn merge(&mut self, path: &Path, original: &[u8], ours: &[u8], theirs: &[u8]) {
+        let text = (
+            std::str::from_utf8(original),
+            std::str::from_utf8(ours),
+            std::str::from_utf8(theirs),
+        );
+        let (Ok(original), Ok(ours_text), Ok(theirs_text)) = text else {
+            // Binary files cannot carry conflict markers: keep ours, write theirs next to it.
+            let mut side = path.as_os_str().to_owned();
+            side.push(SIDE_SUFFIX);
+            self.changes.insert(PathBuf::from(side), Some(theirs.to_vec()));
+            self.summary.conflicted.push(path.to_path_buf());
+            return;
+        };
+
+        let mut options = MergeOptions::new();
+        options.set_conflict_style(ConflictStyle::Merge);
+        match options.merge(original, ours_text, theirs_text) {
+            Ok(merged) => {
+                self.changes.insert(path.to_path_buf(), Some(merged.into_bytes()));
+                self.summary.merged.push(path.to_path_buf());
+            }
+            Err(conflicted) => {
+                self.changes.insert(path.to_path_buf(), Some(conflicted.into_bytes()));
+                self.summary.conflicted.push(path.to_path_buf());
+            }
+        }
+    }
+
+    /// Write every change into `project_dir`.
+    pub fn apply(&self, project_dir: &Path) -> Result<()> {
+        for (path, contents) in &self.changes {
+            let target = project_dir.join(path);
+            match contents {
+                Some(contents) => write_file(&target, contents)?,
+                None => fs::remove_file(&target)
+                    .with_context(|| format!("Failed to remove file: {}", target.display()))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Paths are recorded with `/` separators.
+fn lock_key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn test_plan_merges_template_changes_into_project() {
+        let project = assert_fs::TempDir::new().unwrap();
+        project.child("untouched.txt").write_str("a\n").unwrap();
+        project.child("edited.txt").write_str("one\ntwo changed\nthree\n").unwrap();
+        project.child("clash.txt").write_str("x = 2\n").unwrap();
+        project.child("gone.txt").write_str("old\n").unwrap();
+
+        let base = RenderedProject::from_files(&[
+            ("untouched.txt", "a\n"),
+            ("edited.txt", "one\ntwo\nthree\n"),
+            ("clash.txt", "x = 1\n"),
+            ("gone.txt", "old\n"),
+            ("deleted.txt", "d\n"),
+        ]);
+        let theirs = RenderedProject::from_files(&[
+            ("untouched.txt", "b\n"),
+            ("edited.txt", "one\ntwo\nthree\nfour\n"),
+            ("clash.txt", "x = 3\n"),
+            ("deleted.txt", "d2\n"),
+            ("new.txt", "new\n"),
+        ]);
+
+        let update = Update::plan(project.path(), &Base::Rendered(&base), &theirs).unwrap();
+        assert_eq!(update.summary.updated, vec![PathBuf::from("untouched.txt")]);
+        assert_eq!(update.summary.merged, vec![PathBuf::from("edited.txt")]);
+        assert_eq!(update.summary.conflicted, vec![PathBuf::from("clash.txt")]);
+        assert_eq!(update.summary.added, vec![PathBuf::from("new.txt")]);
+        assert_eq!(update.summary.removed, vec![PathBuf::from("gone.txt")]);
+        assert_eq!(update.summary.deleted_by_user, vec![PathBuf::from("deleted.txt")]);
+
+        update.apply(project.path()).unwrap();
+        project.child("untouched.txt").assert("b\n");
+        project.child("edited.txt").assert("one\ntwo changed\nthree\nfour\n");
+        project
+            .child("clash.txt")
+            .assert("<<<<<<< ours\nx = 2\n=======\nx = 3\n>>>>>>> theirs\n");
+        project.child("gone.txt").assert(predicates::path::missing());
+        project.child("deleted.txt").assert(predicates::path::missing());
+        project.child("new.txt").assert("new\n");
+    }
+
+    #[test]
+    fn test_plan_without_original_uses_recorded_hashes() {
+        let project = assert_fs::TempDir::new().unwrap();
+        project.child("untouched.txt").write_str("a\n").unwrap();
+        project.child("edited.txt").write_str("mine\n").unwrap();
+
+        let hashes = BTreeMap::from([
+            ("untouched.txt".to_string(), sha256_hex(b"a\n")),
+            ("edited.txt".to_string(), sha256_hex(b"generated\n")),
+        ]);
+        let theirs = RenderedProject::from_files(&[("untouched.txt", "b\n"), ("edited.txt", "template\n")]);
+
+        let update = Update::plan(project.path(), &Base::Hashes(&hashes), &theirs).unwrap();
+        assert_eq!(update.summary.updated, vec![PathBuf::from("untouched.txt")]);
+        assert_eq!(update.summary.conflicted, vec![PathBuf::from("edited.txt")]);
+    }
+}
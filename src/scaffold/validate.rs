@@ -0,0 +1,160 @@
+use heck::ToSnakeCase;
+
+/// Rust keywords (strict and reserved, all editions). A crate with one of these
+/// names cannot be imported with `use <name>::...`.
+pub(super) const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Crates shipped with the toolchain; a package with the same name shadows them.
+const STD_CRATES: &[&str] = &["alloc", "core", "proc_macro", "std", "test"];
+
+/// Names Cargo refuses because they collide with its own output directories.
+const CARGO_RESERVED: &[&str] = &["build", "deps", "examples", "incremental"];
+
+/// Device names that cannot be used as file names on Windows.
+const WINDOWS_RESERVED: &[&str] = &[
+    "aux", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9", "con", "lpt1",
+    "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9", "nul", "prn",
+];
+
+/// Check that `name` is usable as the generated package name and as a crate identifier.
+///
+/// `dependencies` are the crates the template pulls in; a package with the same
+/// crate identifier as one of them would shadow it. On failure the error explains
+/// why and, where possible, suggests a `--name` that would work.
+pub fn validate_package_name(name: &str, dependencies: &[String]) -> anyhow::Result<()> {
+    let Err(reason) = check_package_name(name, dependencies) else {
+        return Ok(());
+    };
+
+    let mut message = format!("Invalid project name '{}': {}.", name, reason);
+    if let Some(suggestion) = suggest(name, dependencies) {
+        message.push_str(&format!(
+            "\n  help: pass `--name {}` to use a different package name",
+            suggestion
+        ));
+    }
+    anyhow::bail!(message)
+}
+
+/// Why `name` cannot be the package name, if it cannot; the reason without the suggestion
+/// [`validate_package_name`] adds.
+pub fn check_package_name(name: &str, dependencies: &[String]) -> Result<(), String> {
+    let first = name.chars().next().ok_or("the name cannot be empty")?;
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+    {
+        return Err(format!(
+            "invalid character `{}`, only ASCII letters, digits, `-` and `_` are allowed",
+            c
+        ));
+    }
+    if first.is_ascii_digit() {
+        return Err("the name cannot start with a digit".to_string());
+    }
+
+    let ident = name.to_snake_case();
+    if RUST_KEYWORDS.contains(&name) || RUST_KEYWORDS.contains(&ident.as_str()) {
+        return Err(format!("`{}` is a Rust keyword", name));
+    }
+    if STD_CRATES.contains(&ident.as_str()) {
+        return Err(format!("`{}` conflicts with Rust's built-in `{}` crate", name, ident));
+    }
+    if CARGO_RESERVED.contains(&name) {
+        return Err(format!("`{}` is reserved by Cargo", name));
+    }
+    if WINDOWS_RESERVED.contains(&name.to_ascii_lowercase().as_str()) {
+        return Err(format!("`{}` is a reserved file name on Windows", name));
+    }
+    if let Some(dep) = dependencies.iter().find(|dep| dep.to_snake_case() == ident) {
+        return Err(format!(
+            "it clashes with the `{}` dependency the template pulls in",
+            dep
+        ));
+    }
+    Ok(())
+}
+
+/// Derive a valid name from an invalid one, if a sensible fix exists.
+fn suggest(name: &str, dependencies: &[String]) -> Option<String> {
+    let mut fixed = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            fixed.push(c.to_ascii_lowercase());
+        } else if !fixed.ends_with('-') {
+            fixed.push('-');
+        }
+    }
+    let mut fixed = fixed.trim_matches('-').to_string();
+    if fixed.is_empty() {
+        return None;
+    }
+    if fixed.starts_with(|c: char| c.is_ascii_digit()) {
+        fixed = format!("app-{}", fixed);
+    }
+    if check_package_name(&fixed, dependencies).is_err() {
+        fixed = format!("{}-app", fixed);
+    }
+    check_package_name(&fixed, dependencies).ok().map(|_| fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(name: &str, deps: &[&str]) -> String {
+        let deps: Vec<String> = deps.iter().map(|d| d.to_string()).collect();
+        validate_package_name(name, &deps).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_accepts_regular_names() {
+        for name in ["my-app", "my_app", "blog2", "MyApp"] {
+            assert!(validate_package_name(name, &["axum".to_string()]).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_names_with_suggestions() {
+        let err = error("123app", &[]);
+        assert!(err.contains("cannot start with a digit"), "{}", err);
+        assert!(err.contains("--name app-123app"), "{}", err);
+
+        let err = error("my app", &[]);
+        assert!(err.contains("invalid character ` `"), "{}", err);
+        assert!(err.contains("--name my-app"), "{}", err);
+
+        let err = error("fn", &[]);
+        assert!(err.contains("Rust keyword"), "{}", err);
+        assert!(err.contains("--name fn-app"), "{}", err);
+
+        let err = error("test", &[]);
+        assert!(err.contains("built-in `test` crate"), "{}", err);
+
+        let err = error("con", &[]);
+        assert!(err.contains("reserved file name on Windows"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_names_clashing_with_dependencies() {
+        let err = error("tracing-subscriber", &["axum", "tracing-subscriber"]);
+        assert!(err.contains("clashes with the `tracing-subscriber` dependency"), "{}", err);
+        assert!(err.contains("--name tracing-subscriber-app"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_package_name_gives_the_bare_reason() {
+        assert_eq!(check_package_name("my-app", &["axum".to_string()]), Ok(()));
+        let reason = check_package_name("self", &[]).unwrap_err();
+        assert!(reason.contains("Rust keyword"), "{}", reason);
+        assert!(!reason.contains("--name"), "{}", reason);
+        let reason = check_package_name("axum", &["axum".to_string()]).unwrap_err();
+        assert!(reason.contains("clashes with the `axum` dependency"), "{}", reason);
+    }
+}
@@ -0,0 +1,97 @@
+use super::source::git;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::path::Path;
+
+/// Version control to set up in a generated project.
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
+#[clap(rename_all = "kebab_case")]
+pub enum Vcs {
+    /// Initialize a git repository
+    Git,
+    /// Do not set up version control
+    None,
+}
+
+/// Whether `dir` is inside a git work tree already (e.g. a package in a monorepo).
+pub fn inside_git_repository(dir: &Path) -> bool {
+    git(Some(dir), &["rev-parse", "--is-inside-work-tree"]).is_ok_and(|out| out == "true")
+}
+
+/// `git init` in `dir`, unless it already belongs to a repository. Returns whether a
+/// repository was created.
+pub fn git_init(dir: &Path) -> Result<bool> {
+    if inside_git_repository(dir) {
+        return Ok(false);
+    }
+    git(Some(dir), &["init", "--quiet"])
+        .with_context(|| format!("Failed to initialize a git repository in '{}'", dir.display()))?;
+    Ok(true)
+}
+
+/// The web URL of the `origin` remote of the repository `dir` is in, if any:
+/// `git@github.com:acme/app.git` becomes `https://github.com/acme/app`.
+pub fn origin_url(dir: &Path) -> Option<String> {
+    if !dir.is_dir() {
+        return None;
+    }
+    let url = git(Some(dir), &["config", "--get", "remote.origin.url"]).ok()?;
+    web_url(&url)
+}
+
+fn web_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    if let Some(rest) = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) {
+        // Drop credentials such as `https://token@host/...`.
+        let rest = rest.split_once('@').filter(|(user, _)| !user.contains('/')).map_or(rest, |(_, r)| r);
+        return Some(format!("https://{}", rest));
+    }
+    let rest = url.strip_prefix("ssh://").unwrap_or(url);
+    let (_, host_path) = rest.split_once('@')?;
+    let (host, path) = host_path.split_once(':').or_else(|| host_path.split_once('/'))?;
+    Some(format!("https://{}/{}", host, path.trim_start_matches('/')))
+}
+
+/// Stage everything in `dir` and commit it, returning the short commit hash.
+pub fn initial_commit(dir: &Path, message: &str) -> Result<String> {
+    git(Some(dir), &["add", "--all", "."])?;
+    git(Some(dir), &["commit", "--quiet", "--message", message]).context(
+        "Failed to create the initial commit; is git's user.name and user.email configured?",
+    )?;
+    git(Some(dir), &["rev-parse", "--short", "HEAD"])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn test_git_init_and_initial_commit() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        dir.child(".gitignore").write_str("target/\n").unwrap();
+        dir.child("target/debug/app").write_str("").unwrap();
+        dir.child("src/main.rs").write_str("fn main() {}\n").unwrap();
+
+        assert!(git_init(dir.path()).unwrap());
+        assert!(!git_init(dir.path()).unwrap());
+        let nested = dir.child("src");
+        assert!(inside_git_repository(nested.path()));
+        assert!(!git_init(nested.path()).unwrap());
+
+        git(Some(dir.path()), &["config", "user.name", "Test"]).unwrap();
+        git(Some(dir.path()), &["config", "user.email", "test@example.com"]).unwrap();
+        initial_commit(dir.path(), "Initial commit").unwrap();
+        let tracked = git(Some(dir.path()), &["ls-files"]).unwrap();
+        assert_eq!(tracked, ".gitignore\nsrc/main.rs");
+    }
+
+    #[test]
+    fn test_web_url() {
+        assert_eq!(web_url("git@github.com:acme/app.git").as_deref(), Some("https://github.com/acme/app"));
+        assert_eq!(web_url("ssh://git@git.acme.dev/tools/app").as_deref(), Some("https://git.acme.dev/tools/app"));
+        assert_eq!(web_url("https://token@github.com/acme/app.git\n").as_deref(), Some("https://github.com/acme/app"));
+        assert_eq!(web_url("/srv/app.git"), None);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::scaffold::manifest::TemplateManifest;
+use crate::scaffold::validate::check_package_name;
+use crate::scaffold::TemplateLocation;
+use anyhow::Result;
+use colored::*;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+
+/// Licenses offered by the wizard, as SPDX expressions.
+pub const LICENSES: &[&str] = &[
+    "MIT OR Apache-2.0",
+    "MIT",
+    "Apache-2.0",
+    "BSD-3-Clause",
+    "MPL-2.0",
+    "GPL-3.0-only",
+];
+
+/// What the wizard asked for.
+pub struct Answers {
+    /// The package name, `project_name` unless changed.
+    pub name: String,
+    pub manifest: TemplateManifest,
+    pub location: TemplateLocation,
+    pub addons: BTreeMap<String, bool>,
+    pub author: String,
+    pub license: Option<String>,
+}
+
+/// Whether someone is at the terminal to answer questions.
+pub fn available() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+/// Ask for frontend, database, add-ons, package name, author and license, then confirm.
+/// Every choice is built from the manifests of `templates`. Returns `None` when the user
+/// declines the confirmation.
+///
+/// `project_name`, `author`, `license` and the add-ons picked by `addons` for the chosen
+/// template are the defaults, so flags and configuration still apply. The package name
+/// must not clash with the crates `dependencies` lists for the chosen template.
+pub fn run(
+    project_name: &str,
+    templates: &[(TemplateManifest, TemplateLocation)],
+    author: &str,
+    license: Option<&str>,
+    addons: impl Fn(&TemplateManifest) -> Result<BTreeMap<String, bool>>,
+    dependencies: impl Fn(&TemplateManifest, &TemplateLocation, &BTreeMap<String, bool>) -> Result<Vec<String>>,
+) -> Result<Option<Answers>> {
+    let theme = ColorfulTheme::default();
+    println!("{}", "No template chosen; let's pick one.".bold());
+
+    let frontends = frontends(templates);
+    if frontends.is_empty() {
+        anyhow::bail!("No templates available");
+    }
+    let default = frontends.iter().position(|f| f == "dioxus").unwrap_or(0);
+    let frontend = &frontends[Select::with_theme(&theme)
+        .with_prompt("Frontend")
+        .items(&frontends)
+        .default(default)
+        .interact()?];
+
+    let choices = database_choices(templates, frontend);
+    let (manifest, location) = if choices.len() == 1 {
+        choices[0].clone()
+    } else {
+        let labels: Vec<String> = choices.iter().map(|(m, _)| database_label(m)).collect();
+        choices[Select::with_theme(&theme)
+            .with_prompt("Database")
+            .items(&labels)
+            .default(0)
+            .interact()?]
+        .clone()
+    };
+
+    let mut selected = addons(&manifest)?;
+    if !selected.is_empty() {
+        let names: Vec<String> = selected.keys().cloned().collect();
+        let labels: Vec<String> = names
+            .iter()
+            .map(|name| format!("{} - {}", name, manifest.addons[name].description))
+            .collect();
+        let defaults: Vec<bool> = names.iter().map(|name| selected[name]).collect();
+        let chosen = MultiSelect::with_theme(&theme)
+            .with_prompt("Add-ons (space to toggle)")
+            .items(&labels)
+            .defaults(&defaults)
+            .interact()?;
+        for (i, name) in names.iter().enumerate() {
+            selected.insert(name.clone(), chosen.contains(&i));
+        }
+    }
+
+    let dependencies = dependencies(&manifest, &location, &selected)?;
+    let name: String = Input::with_theme(&theme)
+        .with_prompt("Package name")
+        .default(project_name.to_string())
+        .validate_with(|input: &String| check_package_name(input.trim(), &dependencies))
+        .interact_text()?;
+    let name = name.trim().to_string();
+
+    let author: String = Input::with_theme(&theme)
+        .with_prompt("Author")
+        .default(author.to_string())
+        .allow_empty(true)
+        .validate_with(|input: &String| validate_author(input))
+        .interact_text()?;
+
+    let mut licenses: Vec<&str> = LICENSES.to_vec();
+    if let Some(license) = license.filter(|l| !licenses.contains(l)) {
+        licenses.insert(0, license);
+    }
+    licenses.push("None");
+    let default = license.and_then(|l| licenses.iter().position(|c| *c == l)).unwrap_or(0);
+    let license = licenses[Select::with_theme(&theme)
+        .with_prompt("License")
+        .items(&licenses)
+        .default(default)
+        .interact()?];
+    let license = (license != "None").then(|| license.to_string());
+
+    let enabled: Vec<&str> = selected
+        .iter()
+        .filter(|(_, on)| **on)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    println!("\n{}", "Summary".bold());
+    let rows = [
+        ("project", name.clone()),
+        ("template", format!("{} ({})", manifest.id, manifest.description)),
+        ("frontend", manifest.frontend.clone()),
+        ("database", manifest.db.clone().unwrap_or_else(|| "none".to_string())),
+        ("add-ons", if enabled.is_empty() { "none".to_string() } else { enabled.join(", ") }),
+        ("author", if author.is_empty() { "-".to_string() } else { author.clone() }),
+        ("license", license.clone().unwrap_or_else(|| "none".to_string())),
+    ];
+    for (key, value) in rows {
+        println!("   {:<9} {}", key.dimmed(), value);
+    }
+    if !Confirm::with_theme(&theme)
+        .with_prompt("Create the project?")
+        .default(true)
+        .interact()?
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(Answers {
+        name,
+        manifest,
+        location,
+        addons: selected,
+        author: author.trim().to_string(),
+        license,
+    }))
+}
+
+/// Every frontend some template targets, sorted.
+fn frontends(templates: &[(TemplateManifest, TemplateLocation)]) -> Vec<String> {
+    let mut frontends: Vec<String> = templates.iter().map(|(m, _)| m.frontend.clone()).collect();
+    frontends.sort();
+    frontends.dedup();
+    frontends
+}
+
+/// The templates for `frontend`: static sites first, then by database.
+fn database_choices(
+    templates: &[(TemplateManifest, TemplateLocation)],
+    frontend: &str,
+) -> Vec<(TemplateManifest, TemplateLocation)> {
+    let mut choices: Vec<_> = templates
+        .iter()
+        .filter(|(m, _)| m.frontend == frontend)
+        .cloned()
+        .collect();
+    choices.sort_by(|(a, _), (b, _)| (&a.db, &a.id).cmp(&(&b.db, &b.id)));
+    choices
+}
+
+fn database_label(manifest: &TemplateManifest) -> String {
+    format!(
+        "{:<9} {} [{}]",
+        manifest.db.as_deref().unwrap_or("none"),
+        manifest.description,
+        manifest.id
+    )
+}
+
+/// An author is a name, optionally followed by `<email>`, on a single line.
+fn validate_author(input: &str) -> Result<(), String> {
+    let input = input.trim();
+    if input.chars().any(char::is_control) {
+        return Err("The author must fit on one line".to_string());
+    }
+    match input.find('<') {
+        Some(start) if !input.ends_with('>') || !input[start + 1..input.len() - 1].contains('@') => {
+            Err("Use `Name <email@example.com>`".to_string())
+        }
+        None if input.contains('>') => Err("Use `Name <email@example.com>`".to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::source::EmbeddedSource;
+    use crate::scaffold::TEMPLATES;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn template(id: &str, frontend: &str, db: Option<&str>) -> (TemplateManifest, TemplateLocation) {
+        let manifest = TemplateManifest::parse(&format!(
+            "id = \"{}\"\ndescription = \"\"\nfrontend = \"{}\"\n{}",
+            id,
+            frontend,
+            db.map(|db| format!("db = \"{}\"\n", db)).unwrap_or_default()
+        ))
+        .unwrap();
+        let location = TemplateLocation {
+            source: Arc::new(EmbeddedSource::new(&TEMPLATES)),
+            path: PathBuf::from(id),
+        };
+        (manifest, location)
+    }
+
+    #[test]
+    fn test_choices_come_from_manifests() {
+        let templates = vec![
+            template("postgres-htmx", "htmx", Some("postgres")),
+            template("static-htmx", "htmx", None),
+            template("static-dioxus", "dioxus", None),
+            template("house", "leptos", Some("sqlite")),
+        ];
+        assert_eq!(frontends(&templates), vec!["dioxus", "htmx", "leptos"]);
+
+        let ids: Vec<String> = database_choices(&templates, "htmx")
+            .into_iter()
+            .map(|(m, _)| m.id)
+            .collect();
+        assert_eq!(ids, vec!["static-htmx", "postgres-htmx"]);
+    }
+
+    #[test]
+    fn test_validate_author() {
+        assert!(validate_author("").is_ok());
+        assert!(validate_author("Ada Lovelace").is_ok());
+        assert!(validate_author("Ada Lovelace <ada@example.com>").is_ok());
+        assert!(validate_author("Ada <ada@example.com").is_err());
+        assert!(validate_author("Ada <ada>").is_err());
+        assert!(validate_author("Ada\tLovelace").is_err());
+    }
+}
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS {{ resource.plural }} (
+{% if db == "mysql" %}
+    id INT PRIMARY KEY AUTO_INCREMENT,
+{% else %}
+    id SERIAL PRIMARY KEY,
+{% endif %}
+{% for field in resource.fields %}
+    {{ field.name }} {{ field.sql_type }} NOT NULL{{ " DEFAULT FALSE" if field.kind == "bool" }}{{ "," if not loop.last }}
+{% endfor %}
+);
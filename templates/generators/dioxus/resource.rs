@@ -0,0 +1,251 @@
+{% set r = resource %}
+{% set id_type = "i32" if db in ["postgres", "mysql"] else "String" %}
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A {{ r.label }}, shared by the server functions and the components.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct {{ r.pascal }} {
+{% if db == "firebase" %}
+    /// The key of the record; Firebase stores it outside the record.
+    #[serde(default)]
+{% endif %}
+    pub id: {{ id_type }},
+{% for field in r.fields %}
+    pub {{ field.name }}: {{ field.rust_type }},
+{% endfor %}
+}
+
+// Declarations for the client; the server functions are implemented in
+// src/{{ r.plural }}_server.rs.
+#[server(List{{ r.plural_pascal }}, "/api")]
+pub async fn list_{{ r.plural }}() -> Result<Vec<{{ r.pascal }}>, ServerFnError> {
+    unreachable!()
+}
+
+#[allow(unused_variables)]
+#[server(Get{{ r.pascal }}, "/api")]
+pub async fn get_{{ r.snake }}(id: {{ id_type }}) -> Result<{{ r.pascal }}, ServerFnError> {
+    unreachable!()
+}
+
+#[allow(unused_variables)]
+#[server(Create{{ r.pascal }}, "/api")]
+pub async fn create_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    unreachable!()
+}
+
+#[allow(unused_variables)]
+#[server(Update{{ r.pascal }}, "/api")]
+pub async fn update_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    unreachable!()
+}
+
+#[allow(unused_variables)]
+#[server(Delete{{ r.pascal }}, "/api")]
+pub async fn delete_{{ r.snake }}(id: {{ id_type }}) -> Result<(), ServerFnError> {
+    unreachable!()
+}
+
+/// Every {{ r.label }} in a table, the selected one in detail, and a form to create or edit one.
+#[component]
+pub fn {{ r.pascal }}List() -> Element {
+    let mut {{ r.plural }} = use_resource(list_{{ r.plural }});
+    let mut selected = use_signal(|| None::<{{ id_type }}>);
+    let mut editing = use_signal(|| None::<{{ r.pascal }}>);
+
+    let table = match &*{{ r.plural }}.read_unchecked() {
+        Some(Ok(list)) if list.is_empty() => rsx! { p { class: "text-gray-400", "No {{ r.plural_label }} yet." } },
+        Some(Ok(list)) => rsx! {
+            table { class: "w-full text-left",
+                thead {
+                    tr {
+{% for field in r.fields %}
+                        th { class: "p-2", "{{ field.label }}" }
+{% endfor %}
+                        th {}
+                    }
+                }
+                tbody {
+                    for {{ r.snake }} in list.iter().cloned() {
+                        {{ r.pascal }}Row {
+                            key: "{ {{- r.snake }}.id}",
+                            {{ r.snake }}: {{ r.snake }},
+                            on_show: move |id| selected.set(Some(id)),
+                            on_edit: move |{{ r.snake }}| editing.set(Some({{ r.snake }})),
+                            on_deleted: move |_| {{ r.plural }}.restart(),
+                        }
+                    }
+                }
+            }
+        },
+        Some(Err(e)) => rsx! { p { class: "text-red-500", "Error loading {{ r.plural_label }}: {e}" } },
+        None => rsx! { p { "Loading..." } },
+    };
+
+    rsx! {
+        section { class: "text-left",
+            h2 { class: "text-3xl font-bold mb-4", "{{ r.plural_title }}" }
+            {table}
+            if let Some(id) = selected() {
+                {{ r.pascal }}Detail { id: id, on_close: move |_| selected.set(None) }
+            }
+            {{ r.pascal }}Form {
+                {{ r.snake }}: editing(),
+                on_saved: move |_| {
+                    editing.set(None);
+                    {{ r.plural }}.restart();
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn {{ r.pascal }}Row(
+    {{ r.snake }}: {{ r.pascal }},
+    on_show: EventHandler<{{ id_type }}>,
+    on_edit: EventHandler<{{ r.pascal }}>,
+    on_deleted: EventHandler<()>,
+) -> Element {
+    let id = {{ r.snake }}.id.clone();
+    let shown = id.clone();
+    let edited = {{ r.snake }}.clone();
+
+    rsx! {
+        tr { class: "border-t border-gray-700",
+{% for field in r.fields %}
+{% if field.kind == "bool" %}
+            td { class: "p-2", if {{ r.snake }}.{{ field.name }} { "Yes" } else { "No" } }
+{% else %}
+            td { class: "p-2", "{ {{- r.snake }}.{{ field.name }}}" }
+{% endif %}
+{% endfor %}
+            td { class: "p-2 text-right whitespace-nowrap",
+                button { class: "text-blue-400 hover:underline mr-4", onclick: move |_| on_show.call(shown.clone()), "Show" }
+                button { class: "text-blue-400 hover:underline mr-4", onclick: move |_| on_edit.call(edited.clone()), "Edit" }
+                button {
+                    class: "text-red-400 hover:underline",
+                    onclick: move |_| {
+                        let id = id.clone();
+                        async move {
+                            if delete_{{ r.snake }}(id).await.is_ok() {
+                                on_deleted.call(());
+                            }
+                        }
+                    },
+                    "Delete"
+                }
+            }
+        }
+    }
+}
+
+/// One {{ r.label }}, loaded from the server.
+#[component]
+pub fn {{ r.pascal }}Detail(id: ReadOnlySignal<{{ id_type }}>, on_close: EventHandler<()>) -> Element {
+    let {{ r.snake }} = use_resource(move || get_{{ r.snake }}(id()));
+
+    let details = match &*{{ r.snake }}.read_unchecked() {
+        Some(Ok({{ r.snake }})) => rsx! {
+            dl { class: "grid grid-cols-3 gap-4",
+{% for field in r.fields %}
+                dt { class: "text-gray-400", "{{ field.label }}" }
+{% if field.kind == "bool" %}
+                dd { class: "col-span-2", if {{ r.snake }}.{{ field.name }} { "Yes" } else { "No" } }
+{% else %}
+                dd { class: "col-span-2 whitespace-pre-wrap", "{ {{- r.snake }}.{{ field.name }}}" }
+{% endif %}
+{% endfor %}
+            }
+        },
+        Some(Err(e)) => rsx! { p { class: "text-red-500", "Error loading {{ r.label }}: {e}" } },
+        None => rsx! { p { "Loading..." } },
+    };
+
+    rsx! {
+        div { class: "mt-8 p-4 border border-gray-600 rounded-lg",
+            {details}
+            button { class: "mt-4 text-blue-400 hover:underline", onclick: move |_| on_close.call(()), "Close" }
+        }
+    }
+}
+
+/// The form to create a {{ r.label }}, or to edit `{{ r.snake }}`. Calls `on_saved` once the
+/// server accepted it.
+#[component]
+pub fn {{ r.pascal }}Form({{ r.snake }}: ReadOnlySignal<Option<{{ r.pascal }}>>, on_saved: EventHandler<()>) -> Element {
+    let mut draft = use_signal({{ r.pascal }}::default);
+    let mut error = use_signal(|| None::<String>);
+    // Start over whenever another {{ r.label }} is passed in.
+    use_effect(move || draft.set({{ r.snake }}().unwrap_or_default()));
+    let editing = {{ r.snake }}.read().is_some();
+
+    rsx! {
+        form {
+            class: "grid gap-4 mt-8",
+            prevent_default: "onsubmit",
+            onsubmit: move |_| async move {
+                let saved = if editing {
+                    update_{{ r.snake }}(draft()).await
+                } else {
+                    create_{{ r.snake }}(draft()).await
+                };
+                match saved {
+                    Ok(()) => {
+                        draft.set({{ r.pascal }}::default());
+                        error.set(None);
+                        on_saved.call(());
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+            },
+{% for field in r.fields %}
+            label { class: "grid gap-1",
+                span { class: "text-gray-400", "{{ field.label }}" }
+{% if field.kind == "text" %}
+                textarea {
+                    class: "bg-gray-700 text-white p-3 rounded-lg",
+                    rows: "5",
+                    value: "{draft.read().{{ field.name }}}",
+                    oninput: move |evt| draft.write().{{ field.name }} = evt.value(),
+                }
+{% elif field.kind == "bool" %}
+                input {
+                    r#type: "checkbox",
+                    checked: draft.read().{{ field.name }},
+                    oninput: move |evt| draft.write().{{ field.name }} = evt.value() == "true",
+                }
+{% elif field.kind == "string" %}
+                input {
+                    class: "bg-gray-700 text-white p-3 rounded-lg",
+                    r#type: "text",
+                    value: "{draft.read().{{ field.name }}}",
+                    oninput: move |evt| draft.write().{{ field.name }} = evt.value(),
+                }
+{% else %}
+                input {
+                    class: "bg-gray-700 text-white p-3 rounded-lg",
+                    r#type: "number",
+                    step: "{{ "any" if field.kind == "float" else "1" }}",
+                    value: "{draft.read().{{ field.name }}}",
+                    oninput: move |evt| {
+                        if let Ok(value) = evt.value().parse() {
+                            draft.write().{{ field.name }} = value;
+                        }
+                    },
+                }
+{% endif %}
+            }
+{% endfor %}
+            if let Some(e) = error() {
+                p { class: "text-red-500", "Could not save: {e}" }
+            }
+            button {
+                class: "bg-blue-600 hover:bg-blue-700 text-white font-bold p-3 rounded-lg",
+                r#type: "submit",
+                if editing { "Save {{ r.label }}" } else { "Create {{ r.label }}" }
+            }
+        }
+    }
+}
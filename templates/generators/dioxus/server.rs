@@ -0,0 +1,278 @@
+{% set r = resource %}
+use crate::AppState;
+use axum::Extension;
+use dioxus::prelude::*;
+{% if db == "mongodb" %}
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    Collection,
+};
+{% elif db == "firebase" %}
+use std::collections::HashMap;
+{% else %}
+use sqlx::Row;
+{% endif %}
+use {{ project_snake }}::{{ r.plural }}::{{ r.pascal }};
+
+// The server side of the functions declared in src/{{ r.plural }}.rs.
+
+async fn app_state() -> Result<AppState, ServerFnError> {
+    let Extension(state) = extract::<Extension<AppState>, _>().await.map_err(server_error)?;
+    Ok(state)
+}
+
+fn server_error(error: impl std::fmt::Display) -> ServerFnError {
+    ServerFnError::ServerError(error.to_string())
+}
+{% if db == "mongodb" %}
+
+fn collection(state: &AppState) -> Collection<Document> {
+    state.db.collection("{{ r.plural }}")
+}
+
+fn object_id(id: &str) -> Result<ObjectId, ServerFnError> {
+    ObjectId::parse_str(id).map_err(server_error)
+}
+
+fn from_document(document: &Document) -> Result<{{ r.pascal }}, ServerFnError> {
+    Ok({{ r.pascal }} {
+        id: document.get_object_id("_id").map_err(server_error)?.to_hex(),
+{% for field in r.fields %}
+{% set getter = {"string": "get_str", "text": "get_str", "bool": "get_bool", "int": "get_i32", "bigint": "get_i64", "float": "get_f64"}[field.kind] %}
+        {{ field.name }}: document.{{ getter }}("{{ field.name }}").map_err(server_error)?{{ ".to_string()" if field.rust_type == "String" }},
+{% endfor %}
+    })
+}
+
+fn to_document({{ r.snake }}: {{ r.pascal }}) -> Document {
+    doc! {
+{% for field in r.fields %}
+        "{{ field.name }}": {{ r.snake }}.{{ field.name }},
+{% endfor %}
+    }
+}
+
+#[server(List{{ r.plural_pascal }})]
+async fn list_{{ r.plural }}() -> Result<Vec<{{ r.pascal }}>, ServerFnError> {
+    let state = app_state().await?;
+    let documents: Vec<Document> = collection(&state)
+        .find(None, None)
+        .await
+        .map_err(server_error)?
+        .try_collect()
+        .await
+        .map_err(server_error)?;
+    documents.iter().map(from_document).collect()
+}
+
+#[server(Get{{ r.pascal }})]
+async fn get_{{ r.snake }}(id: String) -> Result<{{ r.pascal }}, ServerFnError> {
+    let state = app_state().await?;
+    let document = collection(&state)
+        .find_one(doc! { "_id": object_id(&id)? }, None)
+        .await
+        .map_err(server_error)?
+        .ok_or_else(|| server_error("{{ r.title }} not found"))?;
+    from_document(&document)
+}
+
+#[server(Create{{ r.pascal }})]
+async fn create_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    collection(&state)
+        .insert_one(to_document({{ r.snake }}), None)
+        .await
+        .map_err(server_error)?;
+    Ok(())
+}
+
+#[server(Update{{ r.pascal }})]
+async fn update_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    let filter = doc! { "_id": object_id(&{{ r.snake }}.id)? };
+    collection(&state)
+        .replace_one(filter, to_document({{ r.snake }}), None)
+        .await
+        .map_err(server_error)?;
+    Ok(())
+}
+
+#[server(Delete{{ r.pascal }})]
+async fn delete_{{ r.snake }}(id: String) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    collection(&state)
+        .delete_one(doc! { "_id": object_id(&id)? }, None)
+        .await
+        .map_err(server_error)?;
+    Ok(())
+}
+{% elif db == "firebase" %}
+
+fn url(path: &str) -> Result<String, ServerFnError> {
+    let base = std::env::var("FIREBASE_DB_URL").map_err(server_error)?;
+    Ok(format!("{}/{}.json", base, path))
+}
+
+/// The record as Firebase stores it, without the key.
+fn to_json({{ r.snake }}: &{{ r.pascal }}) -> serde_json::Value {
+    serde_json::json!({
+{% for field in r.fields %}
+        "{{ field.name }}": {{ r.snake }}.{{ field.name }},
+{% endfor %}
+    })
+}
+
+#[server(List{{ r.plural_pascal }})]
+async fn list_{{ r.plural }}() -> Result<Vec<{{ r.pascal }}>, ServerFnError> {
+    let state = app_state().await?;
+    let text = state
+        .http_client
+        .get(url("{{ r.plural }}")?)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(server_error)?
+        .text()
+        .await
+        .map_err(server_error)?;
+    // Firebase returns null when there are no records yet.
+    let records: HashMap<String, {{ r.pascal }}> = serde_json::from_str(&text).unwrap_or_default();
+    let mut {{ r.plural }}: Vec<{{ r.pascal }}> = records
+        .into_iter()
+        .map(|(id, {{ r.snake }})| {{ r.pascal }} { id, ..{{ r.snake }} })
+        .collect();
+    // Firebase push keys sort by creation time.
+    {{ r.plural }}.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok({{ r.plural }})
+}
+
+#[server(Get{{ r.pascal }})]
+async fn get_{{ r.snake }}(id: String) -> Result<{{ r.pascal }}, ServerFnError> {
+    let state = app_state().await?;
+    let text = state
+        .http_client
+        .get(url(&format!("{{ r.plural }}/{}", id))?)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(server_error)?
+        .text()
+        .await
+        .map_err(server_error)?;
+    let {{ r.snake }}: Option<{{ r.pascal }}> = serde_json::from_str(&text).map_err(server_error)?;
+    let {{ r.snake }} = {{ r.snake }}.ok_or_else(|| server_error("{{ r.title }} not found"))?;
+    Ok({{ r.pascal }} { id, ..{{ r.snake }} })
+}
+
+#[server(Create{{ r.pascal }})]
+async fn create_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    state
+        .http_client
+        .post(url("{{ r.plural }}")?)
+        .json(&to_json(&{{ r.snake }}))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(server_error)?;
+    Ok(())
+}
+
+#[server(Update{{ r.pascal }})]
+async fn update_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    state
+        .http_client
+        .put(url(&format!("{{ r.plural }}/{}", {{ r.snake }}.id))?)
+        .json(&to_json(&{{ r.snake }}))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(server_error)?;
+    Ok(())
+}
+
+#[server(Delete{{ r.pascal }})]
+async fn delete_{{ r.snake }}(id: String) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    state
+        .http_client
+        .delete(url(&format!("{{ r.plural }}/{}", id))?)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(server_error)?;
+    Ok(())
+}
+{% else %}
+
+fn from_row(row: &sqlx::{{ "mysql::MySqlRow" if db == "mysql" else "postgres::PgRow" }}) -> Result<{{ r.pascal }}, sqlx::Error> {
+    Ok({{ r.pascal }} {
+        id: row.try_get("id")?,
+{% for field in r.fields %}
+        {{ field.name }}: row.try_get("{{ field.name }}")?,
+{% endfor %}
+    })
+}
+
+#[server(List{{ r.plural_pascal }})]
+async fn list_{{ r.plural }}() -> Result<Vec<{{ r.pascal }}>, ServerFnError> {
+    let state = app_state().await?;
+    let rows = sqlx::query("SELECT id, {{ r.columns }} FROM {{ r.plural }} ORDER BY id")
+        .fetch_all(&state.db_pool)
+        .await
+        .map_err(server_error)?;
+    rows.iter().map(from_row).collect::<Result<_, _>>().map_err(server_error)
+}
+
+#[server(Get{{ r.pascal }})]
+async fn get_{{ r.snake }}(id: i32) -> Result<{{ r.pascal }}, ServerFnError> {
+    let state = app_state().await?;
+    let row = sqlx::query("SELECT id, {{ r.columns }} FROM {{ r.plural }} WHERE id = {{ r.sql.select_id }}")
+        .bind(id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(server_error)?
+        .ok_or_else(|| server_error("{{ r.title }} not found"))?;
+    from_row(&row).map_err(server_error)
+}
+
+#[server(Create{{ r.pascal }})]
+async fn create_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    sqlx::query("INSERT INTO {{ r.plural }} ({{ r.columns }}) VALUES ({{ r.sql.insert_values }})")
+{% for field in r.fields %}
+        .bind({{ r.snake }}.{{ field.name }})
+{% endfor %}
+        .execute(&state.db_pool)
+        .await
+        .map_err(server_error)?;
+    Ok(())
+}
+
+#[server(Update{{ r.pascal }})]
+async fn update_{{ r.snake }}({{ r.snake }}: {{ r.pascal }}) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    sqlx::query("UPDATE {{ r.plural }} SET {{ r.sql.assignments }} WHERE id = {{ r.sql.update_id }}")
+{% for field in r.fields %}
+        .bind({{ r.snake }}.{{ field.name }})
+{% endfor %}
+        .bind({{ r.snake }}.id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(server_error)?;
+    Ok(())
+}
+
+#[server(Delete{{ r.pascal }})]
+async fn delete_{{ r.snake }}(id: i32) -> Result<(), ServerFnError> {
+    let state = app_state().await?;
+    sqlx::query("DELETE FROM {{ r.plural }} WHERE id = {{ r.sql.select_id }}")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(server_error)?;
+    Ok(())
+}
+{% endif %}
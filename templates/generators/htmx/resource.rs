@@ -0,0 +1,405 @@
+{% set r = resource %}
+{% if db == "mongodb" %}
+{% set id_type = "String" %}
+{% set id_arg = "&id" %}
+{% set item_id = r.snake ~ ".id.map(|id| id.to_hex()).unwrap_or_default()" %}
+{% elif db == "firebase" %}
+{% set id_type = "String" %}
+{% set id_arg = "&id" %}
+{% set item_id = r.snake ~ ".id" %}
+{% else %}
+{% set id_type = "i32" %}
+{% set id_arg = "id" %}
+{% set item_id = r.snake ~ ".id" %}
+{% endif %}
+use crate::AppState;
+{% if db == "firebase" %}
+use crate::firebase::{
+    create_{{ r.snake }}, delete_{{ r.snake }}, get_{{ r.snake }}, list_{{ r.plural }}, update_{{ r.snake }}, {{ r.pascal }},
+};
+{% else %}
+use crate::db::{{ r.pascal }};
+{% endif %}
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Form, Router,
+};
+use maud::{html, Markup, DOCTYPE};
+{% if db == "mongodb" %}
+use futures::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    Collection,
+};
+{% endif %}
+use serde::Deserialize;
+
+/// The fields of the form to create or edit a {{ r.label }}.
+#[derive(Deserialize)]
+pub struct {{ r.pascal }}Form {
+{% for field in r.fields %}
+{% if field.kind == "bool" %}
+    #[serde(default)]
+{% endif %}
+    pub {{ field.name }}: {{ field.rust_type }},
+{% endfor %}
+}
+{% if db == "mongodb" or db == "firebase" %}
+
+impl {{ r.pascal }}Form {
+    fn into_{{ r.snake }}(self) -> {{ r.pascal }} {
+        {{ r.pascal }} {
+            id: {{ "None" if db == "mongodb" else "String::new()" }},
+{% for field in r.fields %}
+            {{ field.name }}: self.{{ field.name }},
+{% endfor %}
+        }
+    }
+}
+{% endif %}
+
+/// The /{{ r.plural }} routes, merged into the app's router in main.rs.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/{{ r.plural }}", get(list).post(create))
+        .route("/{{ r.plural }}/new", get(new))
+        .route("/{{ r.plural }}/:id", get(show).put(update).delete(delete))
+        .route("/{{ r.plural }}/:id/edit", get(edit))
+}
+
+async fn list(State(state): State<AppState>, headers: HeaderMap) -> Result<Markup, StatusCode> {
+    let {{ r.plural }} = fetch_all(&state).await?;
+    Ok(index_page(&headers, &{{ r.plural }}, html! {}))
+}
+
+async fn show(
+    State(state): State<AppState>,
+    Path(id): Path<{{ id_type }}>,
+    headers: HeaderMap,
+) -> Result<Markup, StatusCode> {
+    let {{ r.snake }} = fetch_one(&state, {{ id_arg }}).await?;
+    Ok(page(
+        &headers,
+        "{{ r.title }}",
+        html! {
+            h1 class="text-4xl font-bold mb-8" { "{{ r.title }} " ({{ item_id }}) }
+            dl class="grid grid-cols-3 gap-4 mb-8" {
+{% for field in r.fields %}
+                dt class="text-gray-400" { "{{ field.label }}" }
+{% if field.kind == "bool" %}
+                dd class="col-span-2" { @if {{ r.snake }}.{{ field.name }} { "Yes" } @else { "No" } }
+{% else %}
+                dd class="col-span-2 whitespace-pre-wrap" { ({{ r.snake }}.{{ field.name }}) }
+{% endif %}
+{% endfor %}
+            }
+            a href="/{{ r.plural }}" class="text-blue-400 hover:underline" { "Back to {{ r.plural_label }}" }
+        },
+    ))
+}
+
+async fn new(State(state): State<AppState>, headers: HeaderMap) -> Result<Markup, StatusCode> {
+    if is_htmx(&headers) {
+        return Ok(form(None));
+    }
+    let {{ r.plural }} = fetch_all(&state).await?;
+    Ok(index_page(&headers, &{{ r.plural }}, form(None)))
+}
+
+async fn create(State(state): State<AppState>, Form(form): Form<{{ r.pascal }}Form>) -> Result<Markup, StatusCode> {
+    insert(&state, form).await?;
+    Ok(table(&fetch_all(&state).await?))
+}
+
+async fn edit(
+    State(state): State<AppState>,
+    Path(id): Path<{{ id_type }}>,
+    headers: HeaderMap,
+) -> Result<Markup, StatusCode> {
+    let {{ r.snake }} = fetch_one(&state, {{ id_arg }}).await?;
+    if is_htmx(&headers) {
+        return Ok(form(Some(&{{ r.snake }})));
+    }
+    let {{ r.plural }} = fetch_all(&state).await?;
+    Ok(index_page(&headers, &{{ r.plural }}, form(Some(&{{ r.snake }}))))
+}
+
+async fn update(
+    State(state): State<AppState>,
+    Path(id): Path<{{ id_type }}>,
+    Form(form): Form<{{ r.pascal }}Form>,
+) -> Result<Markup, StatusCode> {
+    save(&state, {{ id_arg }}, form).await?;
+    Ok(table(&fetch_all(&state).await?))
+}
+
+async fn delete(State(state): State<AppState>, Path(id): Path<{{ id_type }}>) -> Result<Markup, StatusCode> {
+    remove(&state, {{ id_arg }}).await?;
+    // An empty response swaps the row out of the table.
+    Ok(html! {})
+}
+
+fn internal_error(error: impl std::fmt::Display) -> StatusCode {
+    tracing::error!("{}", error);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+{% if db == "mongodb" %}
+
+fn collection(state: &AppState) -> Collection<{{ r.pascal }}> {
+    state.db.collection("{{ r.plural }}")
+}
+
+fn object_id(id: &str) -> Result<ObjectId, StatusCode> {
+    ObjectId::parse_str(id).map_err(|_| StatusCode::NOT_FOUND)
+}
+
+async fn fetch_all(state: &AppState) -> Result<Vec<{{ r.pascal }}>, StatusCode> {
+    let cursor = collection(state).find(None, None).await.map_err(internal_error)?;
+    cursor.try_collect().await.map_err(internal_error)
+}
+
+async fn fetch_one(state: &AppState, id: &str) -> Result<{{ r.pascal }}, StatusCode> {
+    collection(state)
+        .find_one(doc! { "_id": object_id(id)? }, None)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn insert(state: &AppState, form: {{ r.pascal }}Form) -> Result<(), StatusCode> {
+    collection(state)
+        .insert_one(form.into_{{ r.snake }}(), None)
+        .await
+        .map_err(internal_error)?;
+    Ok(())
+}
+
+async fn save(state: &AppState, id: &str, form: {{ r.pascal }}Form) -> Result<(), StatusCode> {
+    collection(state)
+        .replace_one(doc! { "_id": object_id(id)? }, form.into_{{ r.snake }}(), None)
+        .await
+        .map_err(internal_error)?;
+    Ok(())
+}
+
+async fn remove(state: &AppState, id: &str) -> Result<(), StatusCode> {
+    collection(state)
+        .delete_one(doc! { "_id": object_id(id)? }, None)
+        .await
+        .map_err(internal_error)?;
+    Ok(())
+}
+{% elif db == "firebase" %}
+
+async fn fetch_all(state: &AppState) -> Result<Vec<{{ r.pascal }}>, StatusCode> {
+    list_{{ r.plural }}(&state.http_client).await.map_err(internal_error)
+}
+
+async fn fetch_one(state: &AppState, id: &str) -> Result<{{ r.pascal }}, StatusCode> {
+    get_{{ r.snake }}(&state.http_client, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn insert(state: &AppState, form: {{ r.pascal }}Form) -> Result<(), StatusCode> {
+    create_{{ r.snake }}(&state.http_client, &form.into_{{ r.snake }}())
+        .await
+        .map_err(internal_error)
+}
+
+async fn save(state: &AppState, id: &str, form: {{ r.pascal }}Form) -> Result<(), StatusCode> {
+    update_{{ r.snake }}(&state.http_client, id, &form.into_{{ r.snake }}())
+        .await
+        .map_err(internal_error)
+}
+
+async fn remove(state: &AppState, id: &str) -> Result<(), StatusCode> {
+    delete_{{ r.snake }}(&state.http_client, id)
+        .await
+        .map_err(internal_error)
+}
+{% else %}
+
+async fn fetch_all(state: &AppState) -> Result<Vec<{{ r.pascal }}>, StatusCode> {
+    sqlx::query_as::<_, {{ r.pascal }}>("SELECT id, {{ r.columns }} FROM {{ r.plural }} ORDER BY id")
+        .fetch_all(&state.db_pool)
+        .await
+        .map_err(internal_error)
+}
+
+async fn fetch_one(state: &AppState, id: i32) -> Result<{{ r.pascal }}, StatusCode> {
+    sqlx::query_as::<_, {{ r.pascal }}>("SELECT id, {{ r.columns }} FROM {{ r.plural }} WHERE id = {{ r.sql.select_id }}")
+        .bind(id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(internal_error)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn insert(state: &AppState, form: {{ r.pascal }}Form) -> Result<(), StatusCode> {
+    sqlx::query("INSERT INTO {{ r.plural }} ({{ r.columns }}) VALUES ({{ r.sql.insert_values }})")
+{% for field in r.fields %}
+        .bind(form.{{ field.name }})
+{% endfor %}
+        .execute(&state.db_pool)
+        .await
+        .map_err(internal_error)?;
+    Ok(())
+}
+
+async fn save(state: &AppState, id: i32, form: {{ r.pascal }}Form) -> Result<(), StatusCode> {
+    sqlx::query("UPDATE {{ r.plural }} SET {{ r.sql.assignments }} WHERE id = {{ r.sql.update_id }}")
+{% for field in r.fields %}
+        .bind(form.{{ field.name }})
+{% endfor %}
+        .bind(id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(internal_error)?;
+    Ok(())
+}
+
+async fn remove(state: &AppState, id: i32) -> Result<(), StatusCode> {
+    sqlx::query("DELETE FROM {{ r.plural }} WHERE id = {{ r.sql.select_id }}")
+        .bind(id)
+        .execute(&state.db_pool)
+        .await
+        .map_err(internal_error)?;
+    Ok(())
+}
+{% endif %}
+
+fn is_htmx(headers: &HeaderMap) -> bool {
+    headers.contains_key("hx-request")
+}
+
+/// A whole page for direct visits, or only `content` when HTMX asks for a fragment.
+fn page(headers: &HeaderMap, title: &str, content: Markup) -> Markup {
+    if is_htmx(headers) {
+        return content;
+    }
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                title { (title) " | {{ project_title }}" }
+                meta name="viewport" content="width=device-width, initial-scale=1.0";
+                script src="https://unpkg.com/htmx.org@1.9.12" {}
+                {% if addons.tailwind %}
+                script src="https://cdn.tailwindcss.com" {}
+                {% endif %}
+            }
+            body class="bg-gray-800 text-white flex justify-center py-12" {
+                main class="container mx-auto p-8 border border-gray-600 rounded-lg shadow-xl bg-gray-900 w-2/3" {
+                    (content)
+                }
+            }
+        }
+    }
+}
+
+/// The list of {{ r.plural_label }} with room for the new/edit form below it.
+fn index_page(headers: &HeaderMap, {{ r.plural }}: &[{{ r.pascal }}], form: Markup) -> Markup {
+    page(
+        headers,
+        "{{ r.plural_title }}",
+        html! {
+            div class="flex justify-between items-center mb-8" {
+                h1 class="text-4xl font-bold" { "{{ r.plural_title }}" }
+                button class="bg-blue-600 hover:bg-blue-700 text-white font-bold py-2 px-4 rounded"
+                    hx-get="/{{ r.plural }}/new" hx-target="#{{ r.snake }}-form" {
+                    "New {{ r.label }}"
+                }
+            }
+            (table({{ r.plural }}))
+            div id="{{ r.snake }}-form" class="mt-8" { (form) }
+        },
+    )
+}
+
+fn table({{ r.plural }}: &[{{ r.pascal }}]) -> Markup {
+    html! {
+        div id="{{ r.plural }}" {
+            @if {{ r.plural }}.is_empty() {
+                p class="text-gray-400" { "No {{ r.plural_label }} yet." }
+            } @else {
+                table class="w-full text-left" {
+                    thead {
+                        tr {
+{% for field in r.fields %}
+                            th class="p-2" { "{{ field.label }}" }
+{% endfor %}
+                            th {}
+                        }
+                    }
+                    tbody {
+                        @for {{ r.snake }} in {{ r.plural }} {
+                            tr class="border-t border-gray-700" {
+{% for field in r.fields %}
+{% if field.kind == "bool" %}
+                                td class="p-2" { @if {{ r.snake }}.{{ field.name }} { "Yes" } @else { "No" } }
+{% else %}
+                                td class="p-2" { ({{ r.snake }}.{{ field.name }}) }
+{% endif %}
+{% endfor %}
+                                td class="p-2 text-right whitespace-nowrap" {
+                                    a class="text-blue-400 hover:underline mr-4" href={ "/{{ r.plural }}/" ({{ item_id }}) } { "Show" }
+                                    button class="text-blue-400 hover:underline mr-4"
+                                        hx-get={ "/{{ r.plural }}/" ({{ item_id }}) "/edit" } hx-target="#{{ r.snake }}-form" {
+                                        "Edit"
+                                    }
+                                    button class="text-red-400 hover:underline"
+                                        hx-delete={ "/{{ r.plural }}/" ({{ item_id }}) } hx-target="closest tr" hx-swap="outerHTML"
+                                        hx-confirm="Delete this {{ r.label }}?" {
+                                        "Delete"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The form to create a {{ r.label }}, or to edit `{{ r.snake }}`. Saving replaces the table
+/// and removes the form.
+fn form({{ r.snake }}: Option<&{{ r.pascal }}>) -> Markup {
+    let url = match {{ r.snake }} {
+        Some({{ r.snake }}) => format!("/{{ r.plural }}/{}", {{ item_id }}),
+        None => "/{{ r.plural }}".to_string(),
+    };
+    html! {
+        form class="grid gap-4 text-left"
+            hx-post=[{{ r.snake }}.is_none().then_some(&url)]
+            hx-put=[{{ r.snake }}.is_some().then_some(&url)]
+            hx-target="#{{ r.plural }}" hx-swap="outerHTML"
+            hx-on--after-request="if (event.detail.successful) this.remove()" {
+{% for field in r.fields %}
+            label class="grid gap-1" {
+                span class="text-gray-400" { "{{ field.label }}" }
+{% if field.kind == "text" %}
+                textarea class="bg-gray-700 text-white p-3 rounded-lg" name="{{ field.name }}" rows="5" {
+                    ({{ r.snake }}.map(|{{ r.snake }}| {{ r.snake }}.{{ field.name }}.as_str()).unwrap_or_default())
+                }
+{% elif field.kind == "bool" %}
+                input type="checkbox" name="{{ field.name }}" value="true" checked[{{ r.snake }}.is_some_and(|{{ r.snake }}| {{ r.snake }}.{{ field.name }})];
+{% elif field.kind == "string" %}
+                input class="bg-gray-700 text-white p-3 rounded-lg" type="text" name="{{ field.name }}"
+                    value=[{{ r.snake }}.map(|{{ r.snake }}| {{ r.snake }}.{{ field.name }}.as_str())];
+{% else %}
+                input class="bg-gray-700 text-white p-3 rounded-lg" type="number" step="{{ "any" if field.kind == "float" else "1" }}" name="{{ field.name }}"
+                    value=[{{ r.snake }}.map(|{{ r.snake }}| {{ r.snake }}.{{ field.name }})] required;
+{% endif %}
+            }
+{% endfor %}
+            button type="submit" class="bg-blue-600 hover:bg-blue-700 text-white font-bold p-3 rounded-lg" {
+                @if {{ r.snake }}.is_some() { "Save {{ r.label }}" } @else { "Create {{ r.label }}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,2 @@
+-- Reverts "{{ migration.label }}" with `sqlx migrate revert`.
+-- DROP TABLE tags;
@@ -0,0 +1,6 @@
+-- {{ migration.label }} ({{ "MySQL" if db == "mysql" else "PostgreSQL" }}). Applied once, in order, when the app starts.
+{% if db == "mysql" %}
+-- CREATE TABLE tags (id INT PRIMARY KEY AUTO_INCREMENT, name VARCHAR(255) NOT NULL);
+{% else %}
+-- CREATE TABLE tags (id SERIAL PRIMARY KEY, name TEXT NOT NULL);
+{% endif %}
@@ -0,0 +1,61 @@
+
+{% if db == "mongodb" %}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct {{ resource.pascal }} {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<mongodb::bson::oid::ObjectId>,
+{% elif db == "firebase" %}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct {{ resource.pascal }} {
+    /// The key of the record in Firebase.
+    #[serde(skip)]
+    pub id: String,
+{% else %}
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone, PartialEq)]
+pub struct {{ resource.pascal }} {
+    pub id: i32,
+{% endif %}
+{% for field in resource.fields %}
+    pub {{ field.name }}: {{ field.rust_type }},
+{% endfor %}
+}
+{% if db == "firebase" %}
+
+pub async fn list_{{ resource.plural }}(client: &Client) -> Result<Vec<{{ resource.pascal }}>, reqwest::Error> {
+    let url = format!("{}/{{ resource.plural }}.json", get_db_url());
+    let text = client.get(&url).send().await?.error_for_status()?.text().await?;
+    let records: HashMap<String, {{ resource.pascal }}> = serde_json::from_str(&text).unwrap_or_default();
+    let mut {{ resource.plural }}: Vec<{{ resource.pascal }}> = records
+        .into_iter()
+        .map(|(id, {{ resource.snake }})| {{ resource.pascal }} { id, ..{{ resource.snake }} })
+        .collect();
+    // Firebase push keys sort by creation time.
+    {{ resource.plural }}.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok({{ resource.plural }})
+}
+
+pub async fn get_{{ resource.snake }}(client: &Client, id: &str) -> Result<Option<{{ resource.pascal }}>, reqwest::Error> {
+    let url = format!("{}/{{ resource.plural }}/{}.json", get_db_url(), id);
+    let text = client.get(&url).send().await?.error_for_status()?.text().await?;
+    let {{ resource.snake }}: Option<{{ resource.pascal }}> = serde_json::from_str(&text).unwrap_or_default();
+    Ok({{ resource.snake }}.map(|{{ resource.snake }}| {{ resource.pascal }} { id: id.to_string(), ..{{ resource.snake }} }))
+}
+
+pub async fn create_{{ resource.snake }}(client: &Client, {{ resource.snake }}: &{{ resource.pascal }}) -> Result<(), reqwest::Error> {
+    let url = format!("{}/{{ resource.plural }}.json", get_db_url());
+    client.post(&url).json({{ resource.snake }}).send().await?.error_for_status()?;
+    Ok(())
+}
+
+pub async fn update_{{ resource.snake }}(client: &Client, id: &str, {{ resource.snake }}: &{{ resource.pascal }}) -> Result<(), reqwest::Error> {
+    let url = format!("{}/{{ resource.plural }}/{}.json", get_db_url(), id);
+    client.put(&url).json({{ resource.snake }}).send().await?.error_for_status()?;
+    Ok(())
+}
+
+pub async fn delete_{{ resource.snake }}(client: &Client, id: &str) -> Result<(), reqwest::Error> {
+    let url = format!("{}/{{ resource.plural }}/{}.json", get_db_url(), id);
+    client.delete(&url).send().await?.error_for_status()?;
+    Ok(())
+}
+{% endif %}
@@ -1,10 +1,19 @@
+{% if db %}
+{% set note = {
+  "postgres": "Stored in the 'items' table, created by the migration in migrations/ on startup.",
+  "mysql": "Stored in the 'items' table, created by the migration in migrations/ on startup.",
+  "mongodb": "Connects to the 'items' collection in your MongoDB database.",
+  "firebase": "Connects to the '/items.json' endpoint in your Firebase Realtime DB.",
+}[db] %}
 #![allow(non_snake_case)]
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Item {
+{% if db in ["postgres", "mysql"] %}
     pub id: i32,
+{% endif %}
     pub name: String,
 }
 
@@ -39,7 +48,7 @@ pub fn app(cx: Scope) -> Element {
         body { class: "bg-gray-800 text-white flex justify-center py-12",
             main { class: "container mx-auto p-8 text-center border border-gray-600 rounded-lg shadow-xl bg-gray-900 w-1/2",
                 h1 { class: "text-5xl font-bold mb-4", "Todo List 📝" }
-                p { class: "text-xs mt-4 mb-8 text-gray-500", "Note: Requires an 'items' table with 'id' (serial primary key) and 'name' (text) columns." }
+                p { class: "text-xs mt-4 mb-8 text-gray-500", "{{ note }}" }
 
                 div { id: "item-list", {item_list} }
 
@@ -66,4 +75,36 @@ pub fn app(cx: Scope) -> Element {
             }
         }
     })
-}
\ No newline at end of file
+}
+{% else %}
+#![allow(non_snake_case)]
+use dioxus::prelude::*;
+
+pub fn app() {
+  dioxus_web::launch(App);
+}
+
+pub fn App(cx: Scope) -> Element {
+  let mut count = use_state(cx, || 0);
+
+  cx.render(rsx! {
+    body {
+      class: "bg-gray-900 text-white flex items-center justify-center h-screen",
+      div {
+        class: "text-center",
+        h1 { class: "text-5xl font-bold mb-4", "Hello from Trestle + Dioxus! 🚀"}
+        p {
+          class: "text-xl mb-8",
+          "Count: ",
+          span { class: "font-mono text-green-400", "{count}" }
+        }
+        button {
+          class: "bg-blue-600 hover:bg-blue-700 text-white font-bold py-2 px-4 rounded",
+          onclick: move |_| count += 1,
+          "Click to increment"
+        }
+      }
+    }
+  })
+}
+{% endif %}
@@ -0,0 +1,154 @@
+{% if db %}
+{% if db == "firebase" %}
+mod firebase;
+{% else %}
+mod db;
+{% endif %}
+
+use axum::Router;
+{% if db == "firebase" %}
+use dioxus::prelude::*;
+use firebase::{add_item_to_firebase, get_items_from_firebase, Item};
+use reqwest::Client;
+{% elif db == "mongodb" %}
+use db::{connect_to_mongodb, Item};
+use dioxus::prelude::*;
+use futures::stream::TryStreamExt;
+use mongodb::{Collection, Database};
+{% else %}
+use db::{create_db_pool, Item};
+use dioxus::prelude::*;
+use sqlx::{{ "MySqlPool" if db == "mysql" else "PgPool" }};
+{% endif %}
+use std::net::SocketAddr;
+use tracing::info;
+use {{ project_snake }}::{app, AddItem, GetItems};
+
+#[derive(Clone)]
+struct AppState {
+{% if db == "firebase" %}
+    http_client: Client,
+{% elif db == "mongodb" %}
+    db: Database,
+{% else %}
+    db_pool: {{ "MySqlPool" if db == "mysql" else "PgPool" }},
+{% endif %}
+}
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).init();
+
+{% if db == "firebase" %}
+    let app_state = AppState {
+        http_client: Client::new(),
+    };
+{% elif db == "mongodb" %}
+    let db = connect_to_mongodb().await.expect("DB connection failed");
+    let app_state = AppState { db };
+{% else %}
+    let db_pool = create_db_pool().await.expect("DB connection failed");
+    let app_state = AppState { db_pool };
+{% endif %}
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    info!("🚀 Server listening on http://{}", addr);
+
+    axum::Server::bind(&addr)
+        .serve(
+            Router::new()
+                .register_server_fns("/api")
+                .serve_dioxus_application(ServeConfig::builder().build(), move || {
+                    VirtualDom::new(app)
+                })
+                .with_state(app_state)
+                .into_make_service(),
+        )
+        .await
+        .unwrap();
+}
+
+#[server(GetItems)]
+async fn get_items() -> Result<Vec<Item>, ServerFnError> {
+    let state = axum::extract::Extension::<AppState>::from_request_parts(&mut Default::default(), &mut Default::default())
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?
+        .0;
+
+{% if db == "firebase" %}
+    get_items_from_firebase(&state.http_client)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+{% elif db == "mongodb" %}
+    let collection: Collection<Item> = state.db.collection("items");
+    let mut cursor = collection
+        .find(None, None)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    let mut items = Vec::new();
+    while let Ok(Some(item)) = cursor.try_next().await {
+        items.push(item);
+    }
+    Ok(items)
+{% else %}
+    let items = sqlx::query_as::<_, Item>("SELECT id, name FROM items ORDER BY id")
+        .fetch_all(&state.db_pool)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(items)
+{% endif %}
+}
+
+#[server(AddItem)]
+async fn add_item(name: String) -> Result<(), ServerFnError> {
+    let state = axum::extract::Extension::<AppState>::from_request_parts(&mut Default::default(), &mut Default::default())
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?
+        .0;
+
+{% if db == "firebase" %}
+    let new_item = Item { name };
+    add_item_to_firebase(&state.http_client, &new_item)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))
+{% elif db == "mongodb" %}
+    let collection: Collection<Item> = state.db.collection("items");
+    let new_doc = Item { name };
+    collection
+        .insert_one(new_doc, None)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(())
+{% else %}
+    sqlx::query("INSERT INTO items (name) VALUES ({{ "$1" if db == "postgres" else "?" }})")
+        .bind(name)
+        .execute(&state.db_pool)
+        .await
+        .map_err(|e| ServerFnError::ServerError(e.to_string()))?;
+    Ok(())
+{% endif %}
+}
+{% else %}
+use axum::Router;
+use std::net::SocketAddr;
+use tower_http::services::ServeDir;
+use tracing::info;
+use {{ project_snake }}::app;
+
+#[tokio::main]
+async fn main() {
+  tracing_subscriber::fmt()
+    .with_max_level(tracing::Level::INFO)
+    .init();
+
+  // The router that will serve your static assets
+  let app = Router::new().nest_service("/", ServeDir::new("dist"));
+
+  let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+  info!("🚀 Server listening on http://{}", addr);
+
+  let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+  axum::serve(listener, app).await.unwrap();
+}
+{% endif %}
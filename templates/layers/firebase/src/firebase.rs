@@ -2,7 +2,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, env};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Item {
     pub name: String,
 }
@@ -32,6 +32,6 @@ pub async fn get_items_from_firebase(client: &Client) -> Result<Vec<Item>, reqwe
 
 pub async fn add_item_to_firebase(client: &Client, item: &Item) -> Result<(), reqwest::Error> {
     let url = format!("{}/items.json", get_db_url());
-    client.post(&url).json(item).send().await?;
+    client.post(&url).json(item).send().await?.error_for_status()?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,233 @@
+{% if db %}
+{% set note = {
+  "postgres": "Stored in the 'items' table, created by the migration in migrations/ on startup.",
+  "mysql": "Stored in the 'items' table, created by the migration in migrations/ on startup.",
+  "mongodb": "Connects to the 'items' collection in your MongoDB database.",
+  "firebase": "Connects to the '/items.json' endpoint in your Firebase Realtime DB.",
+}[db] %}
+{% if db == "firebase" %}
+mod firebase;
+{% else %}
+mod db;
+{% endif %}
+
+use axum::{extract::State, routing::{get, post}, Form, Router};
+{% if db == "firebase" %}
+use firebase::{add_item_to_firebase, get_items_from_firebase, Item};
+{% elif db == "mongodb" %}
+use db::{connect_to_mongodb, Item};
+use futures::stream::TryStreamExt;
+{% else %}
+use db::{create_db_pool, Item};
+{% endif %}
+use maud::{html, Markup, DOCTYPE};
+{% if db == "mongodb" %}
+use mongodb::{Collection, Database};
+{% elif db == "firebase" %}
+use reqwest::Client;
+{% endif %}
+use serde::Deserialize;
+{% if db == "postgres" %}
+use sqlx::PgPool;
+{% elif db == "mysql" %}
+use sqlx::MySqlPool;
+{% endif %}
+use std::net::SocketAddr;
+use tracing::info;
+
+#[derive(Clone)]
+struct AppState {
+{% if db == "firebase" %}
+    http_client: Client,
+{% elif db == "mongodb" %}
+    db: Database,
+{% elif db == "mysql" %}
+    db_pool: MySqlPool,
+{% else %}
+    db_pool: PgPool,
+{% endif %}
+}
+
+#[derive(Deserialize)]
+struct NewItem {
+    name: String,
+}
+
+async fn fetch_items(state: &AppState) -> Vec<Item> {
+{% if db == "firebase" %}
+    get_items_from_firebase(&state.http_client)
+        .await
+        .unwrap_or_default()
+{% elif db == "mongodb" %}
+    let collection: Collection<Item> = state.db.collection("items");
+    let mut items = Vec::new();
+    if let Ok(mut cursor) = collection.find(None, None).await {
+        while let Ok(Some(item)) = cursor.try_next().await {
+            items.push(item);
+        }
+    }
+    items
+{% else %}
+    sqlx::query_as::<_, Item>("SELECT id, name FROM items ORDER BY id")
+        .fetch_all(&state.db_pool)
+        .await
+        .unwrap_or_default()
+{% endif %}
+}
+
+async fn root_page(State(state): State<AppState>) -> Markup {
+    let items = fetch_items(&state).await;
+
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                title { "{{ project_title }}" }
+                meta name="viewport" content="width=device-width, initial-scale=1.0";
+                script src="https://unpkg.com/htmx.org@1.9.12" {}
+                {% if addons.tailwind %}
+                script src="https://cdn.tailwindcss.com" {}
+                {% endif %}
+            }
+            body class="bg-gray-800 text-white flex justify-center py-12" {
+                main class="container mx-auto p-8 text-center border border-gray-600 rounded-lg shadow-xl bg-gray-900 w-1/2" {
+                    h1 class="text-5xl font-bold mb-4" { "Todo List 📝" }
+                    p class="text-xs mt-4 mb-8 text-gray-500" { "{{ note }}" }
+
+                    div id="item-list" {
+                        (render_items(items))
+                    }
+
+                    form class="mt-8" hx-post="/items" hx-target="#item-list" hx-swap="outerHTML" hx-on::after-request="this.reset()" {
+                        input type="text" name="name" class="bg-gray-700 text-white p-3 rounded-l-lg" placeholder="New todo item...";
+                        button type="submit" class="bg-blue-600 hover:bg-blue-700 text-white font-bold p-3 rounded-r-lg" { "Add Item" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn add_item(State(state): State<AppState>, Form(item): Form<NewItem>) -> Markup {
+{% if db == "firebase" %}
+    let new_item = Item { name: item.name };
+    let _ = add_item_to_firebase(&state.http_client, &new_item).await;
+{% elif db == "mongodb" %}
+    let collection: Collection<Item> = state.db.collection("items");
+    let new_doc = Item { name: item.name };
+    collection.insert_one(new_doc, None).await.unwrap();
+{% else %}
+    let _ = sqlx::query("INSERT INTO items (name) VALUES ({{ "$1" if db == "postgres" else "?" }})")
+        .bind(item.name)
+        .execute(&state.db_pool)
+        .await;
+{% endif %}
+
+    render_items(fetch_items(&state).await)
+}
+
+fn render_items(items: Vec<Item>) -> Markup {
+    html! {
+        div id="item-list" {
+            @if items.is_empty() {
+                p class="text-gray-400" { "No items in the database yet." }
+            } @else {
+                ul class="list-none text-left" {
+                    @for item in items {
+                        li class="bg-gray-800 p-3 my-2 rounded-lg" { (item.name) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt().with_max_level(tracing::Level::INFO).init();
+
+{% if db == "firebase" %}
+    let app_state = AppState {
+        http_client: Client::new(),
+    };
+{% elif db == "mongodb" %}
+    let db = connect_to_mongodb().await.expect("Failed to connect to MongoDB.");
+    let app_state = AppState { db };
+{% else %}
+    let db_pool = create_db_pool().await.expect("Failed to create database pool.");
+    let app_state = AppState { db_pool };
+{% endif %}
+
+    let app = Router::new()
+        .route("/", get(root_page))
+        .route("/items", post(add_item))
+        .with_state(app_state);
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    info!("🚀 Server listening on http://{}", addr);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
+{% else %}
+use axum::{response::Html, routing::get, Router};
+use maud::{html, Markup, DOCTYPE};
+use std::net::SocketAddr;
+use tracing::info;
+use tower_http::services::ServeDir;
+
+async fn hello_page() -> Markup {
+  html! {
+    (DOCTYPE)
+    html {
+      head {
+        title { "{{ project_title }}" }
+        meta name="viewport" content="width=device-width, initial-scale=1.0";
+        script src="https://unpkg.com/htmx.org@1.9.12" {}
+        {% if addons.tailwind %}
+        script src="https://cdn.tailwindcss.com" {}
+        {% endif %}
+      }
+      body class="bg-gray-900 text-white flex items-center justify-center h-screen" {
+        div class="text-center" {
+          h1 class="text-5xl font-bold mb-4" { "Hello from Trestle! 🚀"}
+          p class="text-xl mb-8" { "This page was rendered by the Axum backend using Maud."}
+          button
+            class="bg-blue-600 hover:bg-blue-700 text-white font-bold py-2 px-4 rounded"
+            hx-get="/clicked"
+            hx-swap="outerHTML"
+          {
+            "Click Me!"
+          }
+        }
+      }
+    }
+  }
+}
+
+async fn clicked_handler() -> Markup {
+  html! {
+    div class="text-center" {
+      p class="text-green-400 text-2xl" { "HTMX is working!" }
+    }
+  }
+}
+
+#[tokio::main]
+async fn main() {
+  tracing_subscriber::fmt()
+    .with_max_level(tracing::Level::INFO)
+    .init();
+
+  let app = Router::new()
+    .route("/", get(hello_page))
+    .route("/clicked", get(clicked_handler));
+
+  let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+  info!("🚀 Server listening on http://{}", addr);
+
+  let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+  axum::serve(listener, app).await.unwrap();
+}
+{% endif %}
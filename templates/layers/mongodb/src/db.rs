@@ -12,7 +12,7 @@ pub async fn connect_to_mongodb() -> Result<Database, mongodb::error::Error> {
     let db_name = env::var("MONGO_DB_NAME").expect("MONGO_DB_NAME must be set in .env file");
 
     let mut client_options = ClientOptions::parse(mongo_uri).await?;
-    client_options.app_name = Some("trestle-app".to_string());
+    client_options.app_name = Some("{{ project_kebab }}".to_string());
 
     let client = Client::with_options(client_options)?;
     Ok(client.database(&db_name))
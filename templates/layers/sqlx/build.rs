@@ -0,0 +1,4 @@
+// Embed migrations added after the last build; `sqlx::migrate!()` reads them at compile time.
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS items (
+{% if db == "mysql" %}
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    name VARCHAR(255) NOT NULL
+{% else %}
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL
+{% endif %}
+);
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+{% if db == "mysql" %}
+use sqlx::mysql::MySqlPoolOptions;
+use sqlx::{FromRow, MySqlPool};
+{% else %}
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{FromRow, PgPool};
+{% endif %}
+use std::env;
+
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Item {
+    pub id: i32,
+    pub name: String,
+}
+
+/// Connect to `DATABASE_URL` and apply the migrations in `migrations/` that have not run yet.
+{% if db == "mysql" %}
+pub async fn create_db_pool() -> Result<MySqlPool, sqlx::Error> {
+    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    let pool = MySqlPoolOptions::new()
+{% else %}
+pub async fn create_db_pool() -> Result<PgPool, sqlx::Error> {
+    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+    let pool = PgPoolOptions::new()
+{% endif %}
+        .max_connections(5)
+        .connect(&db_url)
+        .await?;
+    sqlx::migrate!().run(&pool).await?;
+    Ok(pool)
+}
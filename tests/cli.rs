@@ -2,8 +2,17 @@ use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
 use predicates::prelude::*;
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 
+/// `trestle` run from `root` with an empty user config in `root/config`, so neither the
+/// developer's own config and templates nor a `.trestle.toml` above the checkout leak in.
+fn trestle_cmd(root: &Path) -> Result<Command, Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("trestle")?;
+    cmd.env("XDG_CONFIG_HOME", root.join("config")).current_dir(root);
+    Ok(cmd)
+}
+
 #[test]
 fn test_create_static_htmx() -> Result<(), Box<dyn std::error::Error>> {
     let temp_dir = assert_fs::TempDir::new()?;
@@ -11,12 +20,11 @@ fn test_create_static_htmx() -> Result<(), Box<dyn std::error::Error>> {
     let project_path = temp_dir.child(project_name);
 
     // Run `trestle new my-test-app --frontend htmx`
-    Command::cargo_bin("trestle")?
+    trestle_cmd(temp_dir.path())?
         .arg("new")
         .arg(project_path.path())
         .arg("--frontend")
         .arg("htmx")
-        //.current_dir(temp_dir.path())
         .assert()
         .success();
 
@@ -41,12 +49,11 @@ fn test_create_dioxus_postgres() -> Result<(), Box<dyn std::error::Error>> {
     let project_name = "my-dioxus-pg-app";
     let project_path = temp_dir.child(project_name);
 
-    Command::cargo_bin("trestle")?
+    trestle_cmd(temp_dir.path())?
         .arg("new")
         .arg(project_path.path())
         .arg("--frontend").arg("dioxus")
         .arg("--db").arg("postgres")
-        //.current_dir(temp_dir.path())
         .assert()
         .success();
 
@@ -60,3 +67,1111 @@ fn test_create_dioxus_postgres() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_unknown_stack_lists_available_combinations() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("my-redis-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--db").arg("redis")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--frontend htmx --db postgres"));
+
+    project_path.assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_manifest_is_not_copied_into_project() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("my-mongo-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .arg("--db").arg("mongodb")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Update .env with your credentials"));
+
+    project_path.child("src/db.rs").assert(predicate::path::is_file());
+    project_path.child("trestle-template.toml").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_addon_toggles_render_template_conditionals() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let with_tailwind = temp_dir.child("with-tailwind");
+    let without_tailwind = temp_dir.child("without-tailwind");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new").arg(with_tailwind.path())
+        .arg("--frontend").arg("htmx")
+        .assert()
+        .success();
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new").arg(without_tailwind.path())
+        .arg("--frontend").arg("htmx")
+        .arg("--no-addon").arg("tailwind")
+        .assert()
+        .success();
+
+    let main_rs = fs::read_to_string(with_tailwind.child("src/main.rs").path())?;
+    assert!(main_rs.contains("cdn.tailwindcss.com"));
+    assert!(!main_rs.contains("{%"));
+
+    let main_rs = fs::read_to_string(without_tailwind.child("src/main.rs").path())?;
+    assert!(!main_rs.contains("cdn.tailwindcss.com"));
+    assert!(main_rs.contains("unpkg.com/htmx.org"));
+
+    Ok(())
+}
+
+#[test]
+fn test_dioxus_uses_case_variants_of_hyphenated_name() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("my-dioxus-pg-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--db").arg("postgres")
+        .assert()
+        .success();
+
+    let main_rs = fs::read_to_string(project_path.child("src/main.rs").path())?;
+    assert!(main_rs.contains("use my_dioxus_pg_app::{app, AddItem, GetItems};"));
+
+    let index_html = fs::read_to_string(project_path.child("index.html").path())?;
+    assert!(index_html.contains("<title>My Dioxus Pg App</title>"));
+    assert!(index_html.contains("./pkg/my_dioxus_pg_app_bg.wasm"));
+
+    let cargo_toml = fs::read_to_string(project_path.child("Cargo.toml").path())?;
+    assert!(cargo_toml.contains("name = \"my-dioxus-pg-app\""));
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_project_name_is_rejected_before_writing() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("123app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Initializing").not())
+        .stderr(predicate::str::contains("cannot start with a digit"))
+        .stderr(predicate::str::contains("--name app-123app"));
+
+    project_path.assert(predicate::path::missing());
+
+    let project_path = temp_dir.child("axum");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("clashes with the `axum` dependency"));
+
+    project_path.assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_name_flag_overrides_package_name() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("123app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .arg("--name").arg("app-123app")
+        .assert()
+        .success();
+
+    let cargo_toml = fs::read_to_string(project_path.child("Cargo.toml").path())?;
+    assert!(cargo_toml.contains("name = \"app-123app\""));
+
+    Ok(())
+}
+
+#[test]
+fn test_new_refuses_existing_directory_without_force() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("existing-app");
+    project_path.child("README.md").write_str("# Mine\n")?;
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"))
+        .stderr(predicate::str::contains("--force"));
+
+    project_path.child("Cargo.toml").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_init_writes_side_files_for_conflicts() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("existing-app");
+    project_path.child("README.md").write_str("# Mine\n")?;
+    project_path.child("LICENSE").write_str("MIT\n")?;
+    project_path.child("src/main.rs").write_str("fn main() {}\n")?;
+
+    trestle_cmd(temp_dir.path())?
+        .arg("init")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .arg("--conflict").arg("side")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created"))
+        .stdout(predicate::str::contains("Conflicted"));
+
+    // Package name comes from the directory, existing files are kept
+    let cargo_toml = fs::read_to_string(project_path.child("Cargo.toml").path())?;
+    assert!(cargo_toml.contains("name = \"existing-app\""));
+    project_path.child("README.md").assert("# Mine\n");
+    project_path.child("src/main.rs").assert("fn main() {}\n");
+    project_path.child("src/main.rs.trestle-new").assert(predicate::path::is_file());
+    project_path.child("LICENSE").assert("MIT\n");
+
+    // The kept files were not generated, so there is no stored render to merge from.
+    project_path.child(".trestle/base.tar.gz").assert(predicate::path::missing());
+    trestle_cmd(temp_dir.path())?
+        .arg("diff")
+        .arg(project_path.path())
+        .assert()
+        .code(1)
+        .stdout(
+            predicate::str::contains("comparing with the recorded file hashes")
+                .and(predicate::str::contains("Modified: src/main.rs")),
+        );
+
+    // `new --force` into the same directory skips conflicts when not interactive
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .arg("--force")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped"));
+    project_path.child("src/main.rs").assert("fn main() {}\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_dry_run_prints_tree_and_writes_nothing() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("preview-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .arg("--db").arg("postgres")
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("├── src/"))
+        .stdout(predicate::str::contains("db.rs"))
+        .stdout(predicate::str::contains("layers/sqlx"))
+        .stdout(predicate::str::contains("nothing was written"));
+
+    project_path.assert(predicate::path::missing());
+
+    // With --diff, the existing directory is compared and left untouched
+    project_path.child("Cargo.toml").write_str("[package]\nname = \"old\"\n")?;
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--frontend").arg("htmx")
+        .arg("--dry-run")
+        .arg("--diff")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- a/Cargo.toml"))
+        .stdout(predicate::str::contains("-name = \"old\""))
+        .stdout(predicate::str::contains("+++ b/src/main.rs"));
+
+    project_path.child("Cargo.toml").assert("[package]\nname = \"old\"\n");
+    project_path.child("src").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_user_templates_and_template_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let config_dir = temp_dir.child("config");
+    let house = config_dir.child("trestle/templates/house");
+    house.child("trestle-template.toml").write_str(
+        "id = \"house\"\ndescription = \"Our conventions\"\nfrontend = \"htmx\"\nlayers = [\"layers/base\", \"layers/htmx\"]\nnext_steps = [\"make ci\"]\n",
+    )?;
+    house.child("src/logging.rs").write_str("// logging for {{ project_snake }}\n")?;
+
+    // By id, from the per-user template directory
+    let project_path = temp_dir.child("house-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--template").arg("house")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make ci"));
+
+    project_path.child("src/main.rs").assert(predicate::path::is_file());
+    project_path.child("src/logging.rs").assert("// logging for house_app\n");
+
+    // By path
+    let project_path = temp_dir.child("path-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--template").arg(house.path())
+        .assert()
+        .success();
+
+    project_path.child("src/logging.rs").assert("// logging for path_app\n");
+
+    // Unknown ids list what is available
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(temp_dir.child("nope").path())
+        .arg("--template").arg("does-not-exist")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("house"))
+        .stderr(predicate::str::contains("postgres-htmx"));
+
+    Ok(())
+}
+
+#[test]
+fn test_repo_config_templates_cannot_replace_built_in_ones() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let repo = temp_dir.child("repo");
+    repo.child(".trestle.toml").write_str("template_paths = [\"shared\"]\n")?;
+    for id in ["static-htmx", "house"] {
+        let template = repo.child(format!("shared/{}", id));
+        template.child("trestle-template.toml").write_str(&format!(
+            "id = \"{}\"\ndescription = \"Ours\"\nfrontend = \"htmx\"\nlayers = [\"layers/base\", \"layers/htmx\"]\n",
+            id
+        ))?;
+        template.child("src/shared.rs").write_str("// from the repository\n")?;
+    }
+
+    let project_path = repo.child("site");
+    trestle_cmd(temp_dir.path())?
+        .current_dir(repo.path())
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--template").arg("static-htmx")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Ignoring template 'static-htmx'"));
+
+    project_path.child("src/main.rs").assert(predicate::path::is_file());
+    project_path.child("src/shared.rs").assert(predicate::path::missing());
+
+    // New ids from the repository are still available
+    let project_path = repo.child("house-app");
+    trestle_cmd(temp_dir.path())?
+        .current_dir(repo.path())
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--template").arg("house")
+        .assert()
+        .success();
+
+    project_path.child("src/shared.rs").assert("// from the repository\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_git_template_pinned_to_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let repo = temp_dir.child("house.git");
+    let git = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::new("git")
+            .arg("-C").arg(repo.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()?;
+        assert!(output.status.success(), "{:?}", output);
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    };
+    repo.create_dir_all()?;
+    git(&["init", "--quiet"])?;
+    repo.child("templates/house/trestle-template.toml").write_str(
+        "id = \"house\"\ndescription = \"\"\nfrontend = \"htmx\"\nlayers = [\"layers/base\", \"layers/htmx\"]\n",
+    )?;
+    repo.child("templates/house/VERSION").write_str("v2\n")?;
+    git(&["add", "."])?;
+    git(&["commit", "--quiet", "-m", "v2"])?;
+    git(&["tag", "v2"])?;
+    let v2 = git(&["rev-parse", "HEAD"])?;
+    repo.child("templates/house/VERSION").write_str("v3\n")?;
+    git(&["commit", "--quiet", "-am", "v3"])?;
+
+    let project_path = temp_dir.child("pinned-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--template").arg(format!("git+file://{}#v2", repo.path().display()))
+        .arg("--subdir").arg("templates/house")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(v2.as_str()));
+
+    project_path.child("VERSION").assert("v2\n");
+    project_path.child("src/main.rs").assert(predicate::path::is_file());
+    project_path.child(".git").assert(predicate::path::missing());
+    project_path.child(".trestle/project.toml").assert(
+        predicate::str::contains("kind = \"git\"")
+            .and(predicate::str::contains(format!("commit = \"{}\"", v2)))
+            .and(predicate::str::contains("path = \"templates/house\"")),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_update_merges_template_changes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let repo = temp_dir.child("house.git");
+    let git = |args: &[&str]| -> Result<(), Box<dyn std::error::Error>> {
+        let output = Command::new("git")
+            .arg("-C").arg(repo.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()?;
+        assert!(output.status.success(), "{:?}", output);
+        Ok(())
+    };
+    repo.create_dir_all()?;
+    git(&["init", "--quiet"])?;
+    repo.child("trestle-template.toml").write_str(
+        "id = \"house\"\ndescription = \"\"\nfrontend = \"htmx\"\n",
+    )?;
+    repo.child("NOTES.md").write_str("# {{ project_name }}\n\nintro\n\nfooter\n")?;
+    repo.child("config.toml").write_str("port = 3000\n")?;
+    repo.child("old.txt").write_str("old\n")?;
+    git(&["add", "."])?;
+    git(&["commit", "--quiet", "-m", "v1"])?;
+
+    let project_path = temp_dir.child("house-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--template").arg(format!("git+file://{}", repo.path().display()))
+        .assert()
+        .success();
+
+    project_path.child("NOTES.md").write_str("# house-app\n\nour intro\n\nfooter\n")?;
+    project_path.child("config.toml").write_str("port = 8080\n")?;
+
+    repo.child("NOTES.md").write_str("# {{ project_name }}\n\nintro\n\nfooter v2\n")?;
+    repo.child("config.toml").write_str("port = 4000\n")?;
+    repo.child("new.txt").write_str("new\n")?;
+    git(&["rm", "--quiet", "old.txt"])?;
+    git(&["add", "."])?;
+    git(&["commit", "--quiet", "-m", "v2"])?;
+
+    trestle_cmd(temp_dir.path())?
+        .arg("update")
+        .arg(project_path.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Merged (1)")
+                .and(predicate::str::contains("Conflicts (resolve the <<<<<<< markers) (1)"))
+                .and(predicate::str::contains("Added (1)"))
+                .and(predicate::str::contains("Removed (1)")),
+        );
+
+    project_path.child("NOTES.md").assert("# house-app\n\nour intro\n\nfooter v2\n");
+    project_path
+        .child("config.toml")
+        .assert("<<<<<<< ours\nport = 8080\n=======\nport = 4000\n>>>>>>> theirs\n");
+    project_path.child("new.txt").assert("new\n");
+    project_path.child("old.txt").assert(predicate::path::missing());
+
+    trestle_cmd(temp_dir.path())?
+        .arg("update")
+        .arg(project_path.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Already up to date"));
+
+    Ok(())
+}
+
+#[test]
+fn test_update_merges_from_stored_render_after_template_edited_in_place() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let template = temp_dir.child("house");
+    template.child("trestle-template.toml").write_str(
+        "id = \"house\"\ndescription = \"\"\nfrontend = \"htmx\"\n",
+    )?;
+    template.child("NOTES.md").write_str("# {{ project_name }}\n\nintro\n\nfooter\n")?;
+
+    let project_path = temp_dir.child("house-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .arg("--template").arg(template.path())
+        .assert()
+        .success();
+    project_path.child(".trestle/base.tar.gz").assert(predicate::path::is_file());
+
+    project_path.child("NOTES.md").write_str("# house-app\n\nour intro\n\nfooter\n")?;
+    // The template version the project was generated from is gone after this edit.
+    template.child("NOTES.md").write_str("# {{ project_name }}\n\nintro\n\nfooter v2\n")?;
+
+    trestle_cmd(temp_dir.path())?
+        .arg("update")
+        .arg(project_path.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Merged (1)")
+                .and(predicate::str::contains("no longer available").not()),
+        );
+    project_path.child("NOTES.md").assert("# house-app\n\nour intro\n\nfooter v2\n");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("diff")
+        .arg(project_path.path())
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("+our intro"));
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_reports_drift_with_exit_code() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("drift-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .args(["--frontend", "htmx"])
+        .assert()
+        .success();
+
+    trestle_cmd(temp_dir.path())?
+        .arg("diff")
+        .arg(project_path.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No drift"));
+
+    let main_rs = project_path.child("src/main.rs");
+    let contents = fs::read_to_string(main_rs.path())?;
+    main_rs.write_str(&format!("{}// local change\n", contents))?;
+    project_path.child("src/extra.rs").write_str("")?;
+    fs::remove_file(project_path.child("Cargo.toml").path())?;
+
+    trestle_cmd(temp_dir.path())?
+        .arg("diff")
+        .arg(project_path.path())
+        .assert()
+        .code(1)
+        .stdout(
+            predicate::str::contains("+++ b/src/main.rs")
+                .and(predicate::str::contains("+// local change"))
+                .and(predicate::str::contains("+ src/extra.rs"))
+                .and(predicate::str::contains("- Cargo.toml")),
+        );
+
+    trestle_cmd(temp_dir.path())?
+        .arg("diff")
+        .arg(project_path.path())
+        .arg("--stat")
+        .assert()
+        .code(1)
+        .stdout(
+            predicate::str::contains("1 file(s) changed, 1 insertion(s)(+), 0 deletion(s)(-)")
+                .and(predicate::str::contains("+++ b/src/main.rs").not()),
+        );
+
+    Ok(())
+}
+
+#[test]
+fn test_hooks_run_after_generation_unless_disabled() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    let with_hooks = temp_dir.child("with-hooks");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(with_hooks.path())
+        .args(["--frontend", "htmx", "--db", "postgres"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Running hook: cp .env.example .env"));
+    let env_example = fs::read_to_string(with_hooks.child(".env.example").path())?;
+    with_hooks.child(".env").assert(env_example);
+
+    let without_hooks = temp_dir.child("without-hooks");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(without_hooks.path())
+        .args(["--frontend", "htmx", "--db", "postgres", "--no-hooks"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2. cp .env.example .env"));
+    without_hooks.child(".env").assert(predicate::path::missing());
+
+    let house = temp_dir.child("house");
+    house.child("trestle-template.toml").write_str(
+        "id = \"house\"\ndescription = \"\"\nfrontend = \"htmx\"\n\n\
+         [[hooks]]\nrun = \"echo hello from {{ project_name }}\"\n\n\
+         [[hooks]]\nrun = \"exit 7\"\n\n\
+         [[hooks]]\nbuiltin = \"git-init\"\n",
+    )?;
+    house.child("README.md").write_str("# {{ project_name }}\n")?;
+
+    // Shell commands of a template that is not built in only run with --allow-hooks.
+    let unconfirmed = temp_dir.child("unconfirmed-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(unconfirmed.path())
+        .arg("--template").arg(house.path())
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hooks that would run (after confirming, or with --allow-hooks):"));
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(unconfirmed.path())
+        .arg("--template").arg(house.path())
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("pass --allow-hooks to run them")
+                .and(predicate::str::contains("echo hello from unconfirmed-app"))
+                .and(predicate::str::contains("Running hook").not()),
+        );
+    unconfirmed.child("README.md").assert("# unconfirmed-app\n");
+
+    let failing = temp_dir.child("failing-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(failing.path())
+        .arg("--template").arg(house.path())
+        .arg("--allow-hooks")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("hello from failing-app"))
+        .stderr(
+            predicate::str::contains("Hook `exit 7` failed")
+                .and(predicate::str::contains("These hooks did not run:\n  git init")),
+        );
+    // Nothing is left behind, so the same command can simply be run again.
+    failing.assert(predicate::path::missing());
+    let leftovers: Vec<_> = fs::read_dir(temp_dir.path())?
+        .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<_, _>>()?;
+    assert!(!leftovers.iter().any(|name| name.contains("trestle-staging")), "{:?}", leftovers);
+
+    Ok(())
+}
+
+#[test]
+fn test_vcs_git_writes_gitignore_and_commits() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("versioned-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .args(["--frontend", "dioxus", "--db", "postgres", "--vcs", "git", "--commit"])
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Initialized a git repository"));
+
+    project_path.child(".gitignore").assert("target/\ndist/\npkg/\n.env\n");
+    project_path.child("_gitignore").assert(predicate::path::missing());
+    project_path.child(".env").assert(predicate::path::is_file());
+
+    let git = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::new("git").arg("-C").arg(project_path.path()).args(args).output()?;
+        assert!(output.status.success(), "{:?}", output);
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let tracked = git(&["ls-files"])?;
+    assert!(tracked.lines().any(|f| f == ".gitignore"), "{}", tracked);
+    assert!(tracked.lines().any(|f| f == "src/main.rs"), "{}", tracked);
+    assert!(!tracked.lines().any(|f| f == ".env"), "{}", tracked);
+    assert_eq!(git(&["rev-list", "--count", "HEAD"])?.trim(), "1");
+
+    let plain = temp_dir.child("plain-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(plain.path())
+        .args(["--frontend", "htmx"])
+        .assert()
+        .success();
+    plain.child(".gitignore").assert("target/\n");
+    plain.child(".git").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_config_defaults_and_precedence() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let config_dir = temp_dir.child("config");
+    let repo = temp_dir.child("repo");
+    repo.create_dir_all()?;
+    let trestle = |dir: &Path| -> Result<Command, Box<dyn std::error::Error>> {
+        let mut cmd = trestle_cmd(temp_dir.path())?;
+        cmd.current_dir(dir);
+        Ok(cmd)
+    };
+
+    trestle(temp_dir.path())?
+        .args(["config", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No configuration set"));
+    trestle(temp_dir.path())?.args(["config", "set", "frontend", "htmx"]).assert().success();
+    trestle(temp_dir.path())?.args(["config", "set", "db", "postgres"]).assert().success();
+    trestle(temp_dir.path())?.args(["config", "set", "addons.tailwind", "false"]).assert().success();
+    trestle(repo.path())?.args(["config", "set", "--repo", "db", "none"]).assert().success();
+    trestle(temp_dir.path())?
+        .args(["config", "set", "colour", "red"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown config key 'colour'"));
+
+    config_dir
+        .child("trestle/config.toml")
+        .assert(predicate::str::contains("frontend = \"htmx\""));
+    repo.child(".trestle.toml").assert("db = \"none\"\n");
+    trestle(temp_dir.path())?
+        .args(["config", "get", "db"])
+        .assert()
+        .success()
+        .stdout("postgres\n");
+    trestle(repo.path())?
+        .args(["config", "get", "db"])
+        .assert()
+        .success()
+        .stdout("none\n");
+    trestle(repo.path())?.args(["config", "get", "author"]).assert().code(1);
+    trestle(repo.path())?
+        .args(["config", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("frontend = htmx"))
+        .stdout(predicate::str::contains("db = none"))
+        .stdout(predicate::str::contains(".trestle.toml"));
+
+    // User config: htmx with postgres, tailwind off.
+    trestle(temp_dir.path())?.args(["new", "user-app"]).assert().success();
+    let lock = fs::read_to_string(temp_dir.child("user-app/.trestle/project.toml").path())?;
+    assert!(lock.contains("id = \"postgres-htmx\""), "{}", lock);
+    assert!(lock.contains("tailwind = false"), "{}", lock);
+
+    // The repo config overrides the database, and flags override both.
+    trestle(repo.path())?.args(["new", "repo-app"]).assert().success();
+    let lock = fs::read_to_string(repo.child("repo-app/.trestle/project.toml").path())?;
+    assert!(lock.contains("id = \"static-htmx\""), "{}", lock);
+    trestle(repo.path())?
+        .args(["new", "flag-app", "--frontend", "dioxus", "--addon", "tailwind"])
+        .assert()
+        .success();
+    let lock = fs::read_to_string(repo.child("flag-app/.trestle/project.toml").path())?;
+    assert!(lock.contains("id = \"static-dioxus\""), "{}", lock);
+    assert!(lock.contains("tailwind = true"), "{}", lock);
+
+    Ok(())
+}
+
+#[test]
+fn test_package_metadata_and_license_files() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("published-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .args(["--frontend", "htmx", "--author", "Ada Lovelace <ada@example.com>"])
+        .args(["--license", "MIT OR Apache-2.0", "--description", "An internal crate"])
+        .args(["--repository", "https://git.example.com/tools/published-app"])
+        .assert()
+        .success();
+
+    let manifest: toml::Table = fs::read_to_string(project_path.child("Cargo.toml").path())?.parse()?;
+    let package = manifest["package"].as_table().unwrap();
+    assert_eq!(package["authors"][0].as_str(), Some("Ada Lovelace <ada@example.com>"));
+    assert_eq!(package["license"].as_str(), Some("MIT OR Apache-2.0"));
+    assert_eq!(package["description"].as_str(), Some("An internal crate"));
+    assert_eq!(package["repository"].as_str(), Some("https://git.example.com/tools/published-app"));
+    project_path
+        .child("LICENSE-MIT")
+        .assert(predicate::str::contains("Copyright (c) ").and(predicate::str::contains(" Ada Lovelace\n")));
+    project_path
+        .child("LICENSE-Apache-2.0")
+        .assert(predicate::str::contains("Apache License"));
+    project_path.child("LICENSE").assert(predicate::path::missing());
+
+    // Recorded in the lockfile, so the LICENSE files are not drift.
+    trestle_cmd(temp_dir.path())?
+        .arg("diff")
+        .arg(project_path.path())
+        .assert()
+        .success();
+
+    let single = temp_dir.child("gpl-app");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(single.path())
+        .args(["--frontend", "htmx", "--license", "GPL-3.0-only"])
+        .assert()
+        .success();
+    single.child("LICENSE").assert(predicate::str::contains("GNU GENERAL PUBLIC LICENSE"));
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_resource_for_htmx() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("blog");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .args(["--frontend", "htmx", "--db", "postgres", "--no-hooks"])
+        .assert()
+        .success();
+
+    trestle_cmd(temp_dir.path())?
+        .args(["generate", "resource", "Post", "title:string", "body:text", "published:bool"])
+        .current_dir(project_path.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("create src/posts.rs"))
+        .stdout(predicate::str::contains("update src/main.rs"))
+        // The template applies migrations on startup.
+        .stdout(predicate::str::contains("sqlx migrate run").not());
+
+    project_path
+        .child("src/posts.rs")
+        .assert(predicate::str::contains("\"UPDATE posts SET title = $1, body = $2, published = $3 WHERE id = $4\""));
+    project_path
+        .child("src/db.rs")
+        .assert(predicate::str::contains("pub struct Post {\n    pub id: i32,\n    pub title: String,"));
+    project_path
+        .child("src/main.rs")
+        .assert(predicate::str::contains("mod db;\nmod posts;\n"))
+        .assert(predicate::str::contains(".merge(posts::router())\n        .with_state(app_state)"));
+    let mut migrations: Vec<_> = fs::read_dir(project_path.child("migrations").path())?
+        .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<_, _>>()?;
+    migrations.sort();
+    assert_eq!(migrations.len(), 4, "{:?}", migrations);
+    assert!(migrations[0].ends_with("_create_items.down.sql"), "{:?}", migrations);
+    assert!(migrations[3].ends_with("_create_posts.up.sql"), "{:?}", migrations);
+
+    // A second run would clobber the module, and a project without a database has no store.
+    trestle_cmd(temp_dir.path())?
+        .args(["generate", "resource", "Post", "title:string", "--project"])
+        .arg(project_path.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("src/posts.rs already exists"));
+
+    let static_site = temp_dir.child("site");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(static_site.path())
+        .args(["--frontend", "htmx"])
+        .assert()
+        .success();
+    trestle_cmd(temp_dir.path())?
+        .args(["g", "resource", "Post", "title:string", "--project"])
+        .arg(static_site.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no database"));
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_resource_for_dioxus() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("shop");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .args(["--frontend", "dioxus", "--db", "mongodb", "--no-hooks"])
+        .assert()
+        .success();
+
+    trestle_cmd(temp_dir.path())?
+        .args(["generate", "resource", "Product", "name:string", "price:float", "--project"])
+        .arg(project_path.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("create src/products.rs"))
+        .stdout(predicate::str::contains("create src/products_server.rs"))
+        .stdout(predicate::str::contains("products::ProductList {}"));
+
+    // The model is declared once, in the library, and the server module imports it.
+    project_path
+        .child("src/products.rs")
+        .assert(predicate::str::contains("pub struct Product {\n    pub id: String,\n    pub name: String,\n    pub price: f64,\n}"))
+        .assert(predicate::str::contains("pub fn ProductList() -> Element"));
+    project_path
+        .child("src/products_server.rs")
+        .assert(predicate::str::contains("use shop::products::Product;"))
+        .assert(predicate::str::contains("#[server(ListProducts)]"))
+        .assert(predicate::str::contains("struct Product").not());
+    project_path
+        .child("src/lib.rs")
+        .assert(predicate::str::starts_with("#![allow(non_snake_case)]\npub mod products;\n"));
+    project_path
+        .child("src/main.rs")
+        .assert(predicate::str::contains("mod db;\nmod products_server;\n"));
+    project_path.child("migrations").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_sql_templates_ship_migrations() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("shop");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .args(["--frontend", "htmx", "--db", "mysql", "--no-hooks"])
+        .assert()
+        .success();
+    project_path
+        .child("src/db.rs")
+        .assert(predicate::str::contains("sqlx::migrate!().run(&pool).await?;"));
+    project_path
+        .child("build.rs")
+        .assert(predicate::str::contains("cargo:rerun-if-changed=migrations"));
+    // Compile-time checked queries would need the table before the migrations could run.
+    project_path.child("src/main.rs").assert(
+        predicate::str::contains("sqlx::query_as::<_, Item>(")
+            .and(predicate::str::contains("VALUES (?)\")\n        .bind(item.name)"))
+            .and(predicate::str::contains("query!(").not())
+            .and(predicate::str::contains("query_as!(").not()),
+    );
+
+    trestle_cmd(temp_dir.path())?
+        .args(["generate", "migration", "AddPriceToItems", "--project"])
+        .arg(project_path.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_add_price_to_items.up.sql"));
+
+    let mut migrations: Vec<_> = fs::read_dir(project_path.child("migrations").path())?
+        .map(|entry| entry.map(|e| e.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<_, _>>()?;
+    migrations.sort();
+    assert_eq!(migrations.len(), 4, "{:?}", migrations);
+    let items = fs::read_to_string(project_path.child("migrations").path().join(&migrations[1]))?;
+    assert!(items.contains("id INT PRIMARY KEY AUTO_INCREMENT"), "{}", items);
+    // The new migration sorts after the template's, even within the same second.
+    assert!(migrations[2].ends_with("_add_price_to_items.down.sql"), "{:?}", migrations);
+    assert!(migrations[3].ends_with("_add_price_to_items.up.sql"), "{:?}", migrations);
+    let version = |name: &str| name.split('_').next().unwrap().parse::<u64>().unwrap();
+    assert!(version(&migrations[2]) > version(&migrations[0]));
+
+    let document_store = temp_dir.child("docs");
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(document_store.path())
+        .args(["--frontend", "htmx", "--db", "mongodb", "--no-hooks"])
+        .assert()
+        .success();
+    trestle_cmd(temp_dir.path())?
+        .args(["generate", "migration", "add_tags", "--project"])
+        .arg(document_store.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs a PostgreSQL or MySQL project; this one uses mongodb"));
+
+    Ok(())
+}
+
+#[test]
+fn test_new_records_template_and_file_hashes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let project_path = temp_dir.child("locked-app");
+
+    trestle_cmd(temp_dir.path())?
+        .arg("new")
+        .arg(project_path.path())
+        .args(["--frontend", "htmx", "--db", "postgres", "--no-addon", "tailwind"])
+        .assert()
+        .success();
+
+    let lock: toml::Table = fs::read_to_string(project_path.child(".trestle/project.toml").path())?.parse()?;
+    assert_eq!(lock["trestle_version"].as_str(), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(lock["template"]["id"].as_str(), Some("postgres-htmx"));
+    assert_eq!(lock["template"]["kind"].as_str(), Some("embedded"));
+    assert_eq!(lock["template"]["checksum"].as_str().map(str::len), Some(64));
+    assert_eq!(lock["variables"]["project_name"].as_str(), Some("locked-app"));
+    assert_eq!(lock["variables"]["db"].as_str(), Some("postgres"));
+    assert_eq!(lock["variables"]["addons"]["tailwind"].as_bool(), Some(false));
+
+    let files = lock["files"].as_table().unwrap();
+    let main_rs = fs::read(project_path.child("src/main.rs").path())?;
+    let expected = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&main_rs));
+    assert_eq!(files["src/main.rs"].as_str(), Some(expected.as_str()));
+    assert!(!files.contains_key(".trestle/project.toml"));
+
+    Ok(())
+}
+
+#[test]
+fn test_template_pack_install_list_remove() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let config_dir = temp_dir.child("config");
+    let house = temp_dir.child("house");
+    house.child("trestle-template.toml").write_str(
+        "id = \"house\"\ndescription = \"Our conventions\"\nversion = \"1.2\"\nfrontend = \"htmx\"\nlayers = [\"layers/base\", \"layers/htmx\"]\n",
+    )?;
+    house.child("src/logging.rs").write_str("// logging\n")?;
+
+    trestle_cmd(temp_dir.path())?
+        .args(["template", "pack", "house"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("house-1.2.tar.gz"));
+    temp_dir.child("house-1.2.tar.gz").assert(predicate::path::is_file());
+    temp_dir.child("house-1.2.tar.gz.sha256").assert(predicate::path::is_file());
+
+    // The archive works as a template without installing it
+    trestle_cmd(temp_dir.path())?
+        .args(["new", "from-archive", "--template", "./house-1.2.tar.gz"])
+        .assert()
+        .success();
+    temp_dir.child("from-archive/src/logging.rs").assert("// logging\n");
+    temp_dir.child("from-archive/src/main.rs").assert(predicate::path::is_file());
+
+    trestle_cmd(temp_dir.path())?
+        .args(["template", "install", "house-1.2.tar.gz"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed 'house'"));
+    config_dir
+        .child("trestle/templates/house/src/logging.rs")
+        .assert("// logging\n");
+
+    trestle_cmd(temp_dir.path())?
+        .args(["template", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("house 1.2  Our conventions"));
+
+    trestle_cmd(temp_dir.path())?
+        .args(["new", "from-installed", "--template", "house"])
+        .assert()
+        .success();
+    temp_dir.child("from-installed/src/logging.rs").assert("// logging\n");
+
+    trestle_cmd(temp_dir.path())?
+        .args(["template", "remove", "house"])
+        .assert()
+        .success();
+    config_dir.child("trestle/templates/house").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_template_ids_must_stay_inside_the_templates_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+    let manifest = "id = \"../x\"\ndescription = \"\"\nfrontend = \"htmx\"\n";
+    temp_dir.child("escaped/trestle-template.toml").write_str(manifest)?;
+
+    trestle_cmd(temp_dir.path())?
+        .args(["template", "pack", "escaped"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid template id '../x'"));
+
+    // An archive packed by hand gets no further.
+    let archive = fs::File::create(temp_dir.child("escaped.tar.gz").path())?;
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(archive, flate2::Compression::default()));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, "trestle-template.toml", manifest.as_bytes())?;
+    builder.into_inner()?.finish()?;
+
+    trestle_cmd(temp_dir.path())?
+        .args(["template", "install", "escaped.tar.gz"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid template id '../x'"));
+    temp_dir.child("config/trestle/x").assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn test_templates_list_shows_matrix_and_json() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = assert_fs::TempDir::new()?;
+
+    trestle_cmd(temp_dir.path())?
+        .args(["templates", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("postgres-htmx"))
+        .stdout(predicate::str::contains("--frontend htmx --db postgres"))
+        .stdout(predicate::str::contains("DATABASE_URL"))
+        .stdout(predicate::str::contains("none  firebase  mongodb  mysql  postgres"));
+
+    let output = trestle_cmd(temp_dir.path())?
+        .args(["template", "list", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["templates"].as_array().map(Vec::len), Some(10));
+    assert_eq!(json["matrix"]["dioxus"]["mongodb"], "mongodb-dioxus");
+    assert_eq!(json["templates"][0]["source"]["kind"], "embedded");
+
+    Ok(())
+}